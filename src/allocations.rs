@@ -1,5 +1,6 @@
 use num::Num;
 use std::ops::AddAssign;
+use std::ops::RangeInclusive;
 use std::ops::SubAssign;
 /*
 Say you have 100 cookies that need to get allocated to 5 people. That can be done as
@@ -9,6 +10,10 @@ Say you have 100 cookies that need to get allocated to 5 people. That can be don
     ...
 This iterator goes through all possible allocations starting with [total, 0, ..., 0]
 and ending with [0, 0, ..., total]
+
+Each item is a fresh Vec. In hot loops use `next_ref` instead, which lends the
+current allocation without cloning it:
+    while let Some(amounts) = iter.next_ref() { ... }
 */
 #[allow(dead_code)]
 pub struct AllocationsIterator<T>
@@ -18,6 +23,7 @@ where
     n: usize,
     total: T,
     current: Vec<T>,
+    started: bool,
     done: bool,
 }
 
@@ -27,15 +33,27 @@ where
 {
     fn new(n: usize, total: T) -> Self {
         let mut current = vec![T::zero(); n];
-        current[0] = total;
+        if let Some(first) = current.first_mut() {
+            *first = total;
+        }
         Self {
             n,
             total,
             current,
+            started: false,
             done: n == 0,
         }
     }
 
+    /// The next allocation, borrowed rather than cloned.
+    pub fn next_ref(&mut self) -> Option<&[T]> {
+        if self.started {
+            self.increment();
+        }
+        self.started = true;
+        (!self.done).then_some(&self.current[..])
+    }
+
     fn increment(&mut self) {
         if self.done {
             return;
@@ -63,12 +81,7 @@ where
     type Item = Vec<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.done {
-            return None;
-        }
-        let current_tuple = self.current.clone();
-        self.increment();
-        Some(current_tuple)
+        self.next_ref().map(<[T]>::to_vec)
     }
 }
 
@@ -78,3 +91,453 @@ where
 {
     AllocationsIterator::new(n, total)
 }
+
+/*
+Like `allocations`, but slot i must get an amount within bounds[i]. E.g. with
+bounds [1..=2, 0..=5, 3..=3] and a total of 6 the allocations are
+    [2, 1, 3]
+    [1, 2, 3]
+Uses the same order as `allocations`, and skips straight past any run of
+allocations which would break a bound rather than generating and filtering.
+*/
+pub struct BoundedAllocations<T>
+where
+    T: Num,
+{
+    lo: Vec<T>,
+    hi: Vec<T>,
+    current: Vec<T>,
+    started: bool,
+    done: bool,
+}
+
+impl<T> BoundedAllocations<T>
+where
+    T: Num + Copy + Clone + PartialOrd + AddAssign + SubAssign,
+{
+    fn new(total: T, bounds: &[RangeInclusive<T>]) -> Self {
+        let lo: Vec<T> = bounds.iter().map(|b| *b.start()).collect();
+        let hi: Vec<T> = bounds.iter().map(|b| *b.end()).collect();
+        let mut current = lo.clone();
+        let done = bounds.is_empty()
+            || bounds.iter().any(|b| b.start() > b.end())
+            || !fill(&mut current, &lo, &hi, total);
+        Self {
+            lo,
+            hi,
+            current,
+            started: false,
+            done,
+        }
+    }
+
+    /// The next allocation, borrowed rather than cloned.
+    pub fn next_ref(&mut self) -> Option<&[T]> {
+        if self.started && !self.done {
+            self.done = !self.increment();
+        }
+        self.started = true;
+        (!self.done).then_some(&self.current[..])
+    }
+
+    /// Treats slots `1..` as an odometer (slot 1 turning fastest) with slot 0
+    /// taking whatever is left. Finds the lowest slot that can be bumped, and
+    /// refills the slots below it as early in the order as possible.
+    fn increment(&mut self) -> bool {
+        let (mut below, mut below_lo, mut below_hi) = (T::zero(), T::zero(), T::zero());
+        for i in 1..self.current.len() {
+            below += self.current[i - 1];
+            below_lo += self.lo[i - 1];
+            below_hi += self.hi[i - 1];
+            // Amount shared by this slot and those below it.
+            let base = below + self.current[i];
+            if base < below_lo {
+                continue;
+            }
+            let mut v = self.current[i] + T::one();
+            if base > below_hi && base - below_hi > v {
+                v = base - below_hi;
+            }
+            if v <= self.hi[i] && v <= base - below_lo {
+                self.current[i] = v;
+                let (lo, hi) = (&self.lo[..i], &self.hi[..i]);
+                return fill(&mut self.current[..i], lo, hi, base - v);
+            }
+        }
+        false
+    }
+}
+
+/// Spreads `total` over `slots`, filling the earliest slots first. Returns
+/// false if it can't be done within the bounds.
+fn fill<T>(slots: &mut [T], lo: &[T], hi: &[T], total: T) -> bool
+where
+    T: Num + Copy + PartialOrd + AddAssign + SubAssign,
+{
+    let min = lo.iter().fold(T::zero(), |acc, &v| acc + v);
+    if total < min {
+        return false;
+    }
+    let mut spare = total - min;
+    for i in 0..slots.len() {
+        let extra = if hi[i] - lo[i] < spare {
+            hi[i] - lo[i]
+        } else {
+            spare
+        };
+        slots[i] = lo[i] + extra;
+        spare -= extra;
+    }
+    spare == T::zero()
+}
+
+impl<T> Iterator for BoundedAllocations<T>
+where
+    T: Num + Copy + Clone + PartialOrd + AddAssign + SubAssign,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ref().map(<[T]>::to_vec)
+    }
+}
+
+pub fn bounded_allocations<T>(total: T, bounds: &[RangeInclusive<T>]) -> BoundedAllocations<T>
+where
+    T: Num + Copy + Clone + PartialOrd + AddAssign + SubAssign,
+{
+    BoundedAllocations::new(total, bounds)
+}
+
+/// `n choose k`, or 0 if `k > n`.
+pub fn binomial(n: u64, k: u64) -> u128 {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+}
+
+/// How many items `allocations(n, total)` yields, by stars and bars.
+pub fn allocation_count(n: usize, total: u64) -> u128 {
+    match n {
+        0 => 0,
+        _ => binomial(total + n as u64 - 1, n as u64 - 1),
+    }
+}
+
+/// How many items `bounded_allocations(total, bounds)` yields, counted
+/// slot by slot without enumerating them.
+pub fn bounded_allocation_count(total: usize, bounds: &[RangeInclusive<usize>]) -> u128 {
+    if bounds.is_empty() {
+        return 0;
+    }
+    // ways[t] = number of ways to allocate t over the slots seen so far
+    let mut ways = vec![0_u128; total + 1];
+    ways[0] = 1;
+    for bound in bounds {
+        let mut prefix = vec![0_u128; total + 2];
+        for t in 0..=total {
+            prefix[t + 1] = prefix[t] + ways[t];
+        }
+        for (t, w) in ways.iter_mut().enumerate() {
+            let hi = t.checked_sub(*bound.start());
+            let lo = t.saturating_sub(*bound.end());
+            *w = match hi {
+                Some(hi) if lo <= hi => prefix[hi + 1] - prefix[lo],
+                _ => 0,
+            };
+        }
+    }
+    ways[total]
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Multisets of size `k` drawn from `0..n` (combinations with repetition), as
+/// non-decreasing lists of indices in lexicographic order.
+pub struct Multisets {
+    n: usize,
+    current: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+impl Multisets {
+    /// The next multiset, borrowed rather than cloned.
+    pub fn next_ref(&mut self) -> Option<&[usize]> {
+        if self.started && !self.done {
+            match self.current.iter().rposition(|&i| i + 1 < self.n) {
+                Some(pos) => {
+                    let v = self.current[pos] + 1;
+                    self.current[pos..].fill(v);
+                }
+                None => self.done = true,
+            }
+        }
+        self.started = true;
+        (!self.done).then_some(&self.current[..])
+    }
+}
+
+impl Iterator for Multisets {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ref().map(<[usize]>::to_vec)
+    }
+}
+
+pub fn multisets(n: usize, k: usize) -> Multisets {
+    Multisets {
+        n,
+        current: vec![0; k],
+        started: false,
+        done: n == 0 && k > 0,
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Every way of splitting `0..n` into non-empty blocks, as restricted growth
+/// strings: `rgs[i]` is the block holding element `i`, and blocks are numbered
+/// in order of their first element. There are Bell(n) of them.
+pub struct SetPartitions {
+    current: Vec<usize>,
+    // prefix_max[i] = max(current[..i])
+    prefix_max: Vec<usize>,
+    started: bool,
+    done: bool,
+}
+
+impl SetPartitions {
+    /// The next partition, borrowed rather than cloned.
+    pub fn next_ref(&mut self) -> Option<&[usize]> {
+        if self.started && !self.done {
+            let n = self.current.len();
+            match (1..n)
+                .rev()
+                .find(|&i| self.current[i] <= self.prefix_max[i])
+            {
+                Some(i) => {
+                    self.current[i] += 1;
+                    let max = self.prefix_max[i].max(self.current[i]);
+                    self.current[i + 1..].fill(0);
+                    self.prefix_max[i + 1..].fill(max);
+                }
+                None => self.done = true,
+            }
+        }
+        self.started = true;
+        (!self.done).then_some(&self.current[..])
+    }
+}
+
+impl Iterator for SetPartitions {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_ref().map(<[usize]>::to_vec)
+    }
+}
+
+pub fn set_partitions(n: usize) -> SetPartitions {
+    SetPartitions {
+        current: vec![0; n],
+        prefix_max: vec![0; n],
+        started: false,
+        done: false,
+    }
+}
+
+/// Turns a restricted growth string from `set_partitions` into its blocks.
+pub fn partition_blocks(rgs: &[usize]) -> Vec<Vec<usize>> {
+    let mut blocks: Vec<Vec<usize>> = vec![];
+    for (element, &block) in rgs.iter().enumerate() {
+        if block == blocks.len() {
+            blocks.push(vec![]);
+        }
+        blocks[block].push(element);
+    }
+    blocks
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Every subset of `0..n` (as a bit mask) in Gray code order, so each subset
+/// differs from the previous one by a single element. Items are `(mask,
+/// flipped)` where `flipped` is the element added or removed, letting sums
+/// over the subset be updated in O(1) per step. The first item is the empty
+/// set with nothing flipped.
+pub struct GraySubsets {
+    n: usize,
+    index: u64,
+    done: bool,
+}
+
+impl Iterator for GraySubsets {
+    type Item = (u64, Option<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let i = self.index;
+        // for n == 64 the last index is u64::MAX, so it cannot be followed by one past the end.
+        match i.checked_add(1) {
+            Some(next) if self.n == 64 || next >> self.n == 0 => self.index = next,
+            _ => self.done = true,
+        }
+        let flipped = (i > 0).then(|| i.trailing_zeros() as usize);
+        Some((i ^ (i >> 1), flipped))
+    }
+}
+
+pub fn gray_subsets(n: usize) -> GraySubsets {
+    assert!(n <= 64, "Gray subsets are limited to 64 elements");
+    GraySubsets {
+        n,
+        index: 0,
+        done: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocations() {
+        let all: Vec<Vec<u32>> = allocations(3, 2).collect();
+        assert_eq!(
+            all,
+            vec![
+                vec![2, 0, 0],
+                vec![1, 1, 0],
+                vec![0, 2, 0],
+                vec![1, 0, 1],
+                vec![0, 1, 1],
+                vec![0, 0, 2]
+            ]
+        );
+        assert_eq!(
+            allocations(4, 100).count() as u128,
+            allocation_count(4, 100)
+        );
+        assert_eq!(allocations(0, 5).count(), 0);
+    }
+
+    #[test]
+    fn test_next_ref() {
+        let mut iter = allocations(4, 100_u32);
+        let mut best = 0;
+        while let Some(amounts) = iter.next_ref() {
+            best = best.max(amounts[0] * amounts[3]);
+        }
+        assert_eq!(best, 2500);
+    }
+
+    #[test]
+    fn test_bounded_allocations() {
+        let all: Vec<_> = bounded_allocations(6, &[1..=2, 0..=5, 3..=3]).collect();
+        assert_eq!(all, vec![vec![2, 1, 3], vec![1, 2, 3]]);
+        assert_eq!(bounded_allocations(10, &[0..=2, 0..=2]).count(), 0);
+
+        // Same as filtering the unbounded allocations
+        let bounds = [0..=3, 2..=4, 0..=10, 1..=1];
+        let expected: Vec<Vec<u32>> = allocations(4, 9)
+            .filter(|a| a.iter().zip(&bounds).all(|(v, b)| b.contains(v)))
+            .collect();
+        assert_eq!(
+            bounded_allocations(9, &bounds).collect::<Vec<_>>(),
+            expected
+        );
+        let bounds: Vec<_> = bounds
+            .iter()
+            .map(|b| *b.start() as usize..=*b.end() as usize)
+            .collect();
+        assert_eq!(bounded_allocation_count(9, &bounds), expected.len() as u128);
+    }
+
+    #[test]
+    fn test_counts() {
+        assert_eq!(binomial(5, 2), 10);
+        assert_eq!(binomial(2, 5), 0);
+        assert_eq!(binomial(60, 30), 118264581564861424);
+        assert_eq!(allocation_count(3, 2), 6);
+        assert_eq!(bounded_allocation_count(6, &[1..=2, 0..=5, 3..=3]), 2);
+    }
+
+    #[test]
+    fn test_multisets() {
+        let all: Vec<_> = multisets(3, 2).collect();
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 1],
+                vec![1, 2],
+                vec![2, 2]
+            ]
+        );
+        assert_eq!(multisets(5, 3).count() as u128, binomial(7, 3));
+        assert_eq!(multisets(3, 0).count(), 1);
+        assert_eq!(multisets(0, 2).count(), 0);
+    }
+
+    #[test]
+    fn test_set_partitions() {
+        let all: Vec<_> = set_partitions(3).collect();
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 0, 0],
+                vec![0, 0, 1],
+                vec![0, 1, 0],
+                vec![0, 1, 1],
+                vec![0, 1, 2]
+            ]
+        );
+        // Bell numbers
+        assert_eq!(set_partitions(6).count(), 203);
+        assert_eq!(
+            partition_blocks(&[0, 1, 0, 2]),
+            vec![vec![0, 2], vec![1], vec![3]]
+        );
+    }
+
+    #[test]
+    fn test_gray_subsets() {
+        let containers = [20, 15, 10, 5, 5];
+        let mut sum = 0;
+        let mut fits = 0;
+        let mut previous = 0;
+        for (mask, flipped) in gray_subsets(containers.len()) {
+            if let Some(i) = flipped {
+                assert_eq!((mask ^ previous).count_ones(), 1);
+                if mask & (1 << i) != 0 {
+                    sum += containers[i];
+                } else {
+                    sum -= containers[i];
+                }
+            }
+            previous = mask;
+            fits += (sum == 25) as usize;
+        }
+        assert_eq!(fits, 4);
+        assert_eq!(gray_subsets(10).count(), 1024);
+        assert_eq!(gray_subsets(0).collect::<Vec<_>>(), vec![(0, None)]);
+
+        // the last subsets of 64 elements, ending with the one at index u64::MAX
+        let last = GraySubsets {
+            index: u64::MAX - 1,
+            ..gray_subsets(64)
+        };
+        assert_eq!(
+            last.collect::<Vec<_>>(),
+            vec![(1 << 63 | 1, Some(1)), (1 << 63, Some(0))]
+        );
+    }
+}
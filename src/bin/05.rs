@@ -3,10 +3,11 @@ extern crate advent_of_code;
 #[allow(unused_imports)]
 use advent_of_code::*;
 
-advent_of_code::solution!(5);
+advent_of_code::solution!(5, parse);
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let (ranges, ingredients) = parse(input);
+type Inventory = (Vec<(usize, usize)>, Vec<usize>);
+
+pub fn part_one((ranges, ingredients): &Inventory) -> Option<usize> {
     Some(
         ingredients
             .iter()
@@ -15,9 +16,13 @@ pub fn part_one(input: &str) -> Option<usize> {
     )
 }
 
-pub fn part_two(input: &str) -> Option<usize> {
-    let (ranges, _) = parse(input);
-    Some(merge_ranges(ranges).iter().map(|(s, e)| e - s + 1).sum())
+pub fn part_two((ranges, _): &Inventory) -> Option<usize> {
+    Some(
+        merge_ranges(ranges.clone())
+            .iter()
+            .map(|(s, e)| e - s + 1)
+            .sum(),
+    )
 }
 
 fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
@@ -31,7 +36,7 @@ fn merge_ranges(mut ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
     })
 }
 
-fn parse(input: &str) -> (Vec<(usize, usize)>, Vec<usize>) {
+pub fn parse(input: &str) -> Inventory {
     parser!(
         section(lines(usize "-" usize))
         section(lines(usize))
@@ -39,20 +44,3 @@ fn parse(input: &str) -> (Vec<(usize, usize)>, Vec<usize>) {
     .parse(input)
     .expect("Failed to parse")
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(3));
-    }
-
-    #[test]
-    fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(14));
-    }
-}
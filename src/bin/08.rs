@@ -1,5 +1,6 @@
 extern crate advent_of_code;
 
+use advent_of_code::template::params::param;
 #[allow(unused_imports)]
 use advent_of_code::*;
 use itertools::Itertools;
@@ -7,14 +8,9 @@ use std::collections::{BTreeSet, HashMap};
 
 advent_of_code::solution!(8);
 
-#[cfg(test)]
-const BOXES: usize = 10;
-#[cfg(not(test))]
-const BOXES: usize = 1000;
-
 pub fn part_one(input: &str) -> Option<usize> {
     let mut boxes = Boxes::parse(input);
-    for (a, b) in boxes.ordered_by_distance(BOXES) {
+    for (a, b) in boxes.ordered_by_distance(param("boxes", 1000)) {
         boxes.combine(&a, &b);
     }
     Some(boxes.circuits_by_size().take(3).product())
@@ -114,20 +110,3 @@ impl Boxes {
 fn distance_sq(a: &Pos3, b: &Pos3) -> isize {
     (b.0 - a.0).pow(2) + (b.1 - a.1).pow(2) + (b.2 - a.2).pow(2)
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(40));
-    }
-
-    #[test]
-    fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(25272));
-    }
-}
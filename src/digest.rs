@@ -0,0 +1,170 @@
+use sha2::Digest as _;
+use std::fmt::Debug;
+
+/// A hash function usable by the parallel search harness (`DigestSearch`,
+/// `digest_search`, `nested_digest_list` and `HashStream`).
+pub trait Digest: Clone + Default + Send + Sync {
+    /// Length of the digest in bytes.
+    const OUTPUT_SIZE: usize;
+    type Output: AsRef<[u8]> + Copy + Debug + Ord + Send + Sync;
+
+    fn update(&mut self, data: impl AsRef<[u8]>);
+
+    fn finalize(self) -> Self::Output;
+
+    fn digest(data: impl AsRef<[u8]>) -> Self::Output {
+        let mut hasher = Self::default();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+#[derive(Clone)]
+pub struct Md5(md5::Context);
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self(md5::Context::new())
+    }
+}
+
+impl Digest for Md5 {
+    const OUTPUT_SIZE: usize = 16;
+    type Output = [u8; 16];
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.consume(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().0
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Sha1(sha1::Sha1);
+
+impl Digest for Sha1 {
+    const OUTPUT_SIZE: usize = 20;
+    type Output = [u8; 20];
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().into()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Sha256(sha2::Sha256);
+
+impl Digest for Sha256 {
+    const OUTPUT_SIZE: usize = 32;
+    type Output = [u8; 32];
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().into()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+const KNOT_SIZE: usize = 256;
+const KNOT_ROUNDS: usize = 64;
+const KNOT_SUFFIX: [u8; 5] = [17, 31, 73, 47, 23];
+
+/// The AoC 2017 knot hash. The input is buffered, as the hash needs all the
+/// lengths before it can start twisting.
+#[derive(Clone, Default)]
+pub struct KnotHash(Vec<u8>);
+
+impl Digest for KnotHash {
+    const OUTPUT_SIZE: usize = 16;
+    type Output = [u8; 16];
+
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.0.extend_from_slice(data.as_ref());
+    }
+
+    fn finalize(mut self) -> Self::Output {
+        self.0.extend_from_slice(&KNOT_SUFFIX);
+        let lengths: Vec<usize> = self.0.iter().map(|&b| b as usize).collect();
+        let sparse = sparse_knot_hash(KNOT_SIZE, &lengths, KNOT_ROUNDS);
+
+        let mut dense = [0; 16];
+        for (byte, block) in dense.iter_mut().zip(sparse.chunks(16)) {
+            *byte = block.iter().fold(0, |acc, &v| acc ^ v as u8);
+        }
+        dense
+    }
+}
+
+/// Twists the list `0..size` by each of `lengths` in turn, for `rounds`
+/// rounds, keeping the position and skip size between rounds.
+pub fn sparse_knot_hash(size: usize, lengths: &[usize], rounds: usize) -> Vec<usize> {
+    let mut list: Vec<usize> = (0..size).collect();
+    let (mut pos, mut skip) = (0, 0);
+
+    for _ in 0..rounds {
+        for &len in lengths {
+            assert!(len <= size, "Knot length {len} is longer than the list");
+            // Rotating the twisted section to the front avoids wrapping indices.
+            list.rotate_left(pos);
+            list[..len].reverse();
+            list.rotate_right(pos);
+            pos = (pos + len + skip) % size;
+            skip += 1;
+        }
+    }
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NibbleIter;
+
+    #[test]
+    fn test_known_digests() {
+        assert_eq!(
+            Md5::digest("abc").to_hex(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            Sha1::digest("abc").to_hex(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            Sha256::digest("abc").to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_sparse_knot_hash() {
+        assert_eq!(sparse_knot_hash(5, &[3, 4, 1, 5], 1), vec![3, 4, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_knot_hash() {
+        for (input, expected) in [
+            ("", "a2582a3a0e66e6e86e3812dcb672a272"),
+            ("AoC 2017", "33efeb34ea91902bb2f59c9920caa6cd"),
+            ("1,2,3", "3efbe78a8d82f29979031a4aa0b16a9d"),
+            ("1,2,4", "63960835bcdc130f0b66d7ff4f6a5a8e"),
+        ] {
+            assert_eq!(KnotHash::digest(input).to_hex(), expected);
+        }
+
+        let mut split = KnotHash::default();
+        split.update("AoC ");
+        split.update("2017");
+        assert_eq!(split.finalize(), KnotHash::digest("AoC 2017"));
+    }
+}
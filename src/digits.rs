@@ -1,19 +1,43 @@
-use num::Num;
+use num::{Num, NumCast, cast};
 
+/// Iterates over the digits of a number, least significant digit first (use
+/// `msd_first` for the written order). Zero has a single digit `0`. For
+/// negative numbers the digits of the magnitude are yielded, and the sign is
+/// available from `is_negative`.
 pub struct DigitIterator<T>
 where
     T: Num,
 {
     current: T,
     base: T,
+    negative: bool,
+    started: bool,
 }
 
 impl<T> DigitIterator<T>
 where
-    T: Num,
+    T: Num + PartialOrd,
 {
     fn new(n: T, base: T) -> Self {
-        DigitIterator { current: n, base }
+        let negative = n < T::zero();
+        DigitIterator {
+            current: n,
+            base,
+            negative,
+            started: false,
+        }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The remaining digits, most significant digit first.
+    pub fn msd_first(self) -> std::iter::Rev<std::vec::IntoIter<T>>
+    where
+        T: Copy,
+    {
+        self.collect::<Vec<_>>().into_iter().rev()
     }
 }
 
@@ -25,10 +49,21 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current == T::zero() {
-            None
+            if self.started {
+                return None;
+            }
+            self.started = true;
+            return Some(T::zero());
+        }
+
+        // Remainders of negative numbers are negative (or zero), so flip them
+        // rather than negating `n` up front, which could overflow.
+        let digit = self.current % self.base;
+        self.current = self.current / self.base;
+        self.started = true;
+        if self.negative {
+            Some(T::zero() - digit)
         } else {
-            let digit = self.current % self.base;
-            self.current = self.current / self.base;
             Some(digit)
         }
     }
@@ -36,7 +71,115 @@ where
 
 pub fn digits<T>(n: T, base: T) -> DigitIterator<T>
 where
-    T: Num,
+    T: Num + PartialOrd,
 {
     DigitIterator::new(n, base)
 }
+
+/// Reassembles a number from its digits, most significant digit first.
+pub fn from_digits<T, I>(digits: I, base: T) -> T
+where
+    T: Num + Copy,
+    I: IntoIterator<Item = T>,
+{
+    digits.into_iter().fold(T::zero(), |n, d| n * base + d)
+}
+
+/// The number of digits needed to write `n` (ignoring any sign).
+pub fn digit_count<T>(n: T, base: T) -> usize
+where
+    T: Num + PartialOrd + Copy,
+{
+    digits(n, base).count()
+}
+
+/// Sum of the digits of `n` (ignoring any sign).
+pub fn digit_sum<T>(n: T, base: T) -> T
+where
+    T: Num + PartialOrd + Copy,
+{
+    digits(n, base).fold(T::zero(), |sum, d| sum + d)
+}
+
+/// The digits of `n` in reverse order, keeping its sign. E.g. `-120` → `-21`.
+pub fn reverse_digits<T>(n: T, base: T) -> T
+where
+    T: Num + PartialOrd + Copy,
+{
+    let iter = digits(n, base);
+    let negative = iter.is_negative();
+    let reversed = from_digits(iter, base);
+    if negative {
+        T::zero() - reversed
+    } else {
+        reversed
+    }
+}
+
+/// Concatenates the decimal digits of `a` and `b`, e.g. `13 || 7 = 137`. `b`
+/// must not be negative.
+pub fn concat<T>(a: T, b: T) -> T
+where
+    T: Num + NumCast + PartialOrd + Copy,
+{
+    let ten: T = cast(10).unwrap();
+    let shift = (0..digit_count(b, ten)).fold(T::one(), |s, _| s * ten);
+    if a < T::zero() {
+        a * shift - b
+    } else {
+        a * shift + b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits(1234, 10).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(
+            digits(1234, 10).msd_first().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            digits(0xbeef, 16).msd_first().collect::<Vec<_>>(),
+            vec![11, 14, 14, 15]
+        );
+        assert_eq!(digits(0_u32, 10).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(digits(100, 10).collect::<Vec<_>>(), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_negative_digits() {
+        let d = digits(-305, 10);
+        assert!(d.is_negative());
+        assert_eq!(d.msd_first().collect::<Vec<_>>(), vec![3, 0, 5]);
+        assert_eq!(digits(i8::MIN, 10).collect::<Vec<_>>(), vec![8, 2, 1]);
+    }
+
+    #[test]
+    fn test_from_digits() {
+        assert_eq!(from_digits([1, 3, 7], 10), 137);
+        assert_eq!(from_digits([1, 0, 1], 2), 5);
+        assert_eq!(from_digits(digits(98765_u64, 10).msd_first(), 10), 98765);
+    }
+
+    #[test]
+    fn test_digit_helpers() {
+        assert_eq!(digit_count(0, 10), 1);
+        assert_eq!(digit_count(-999, 10), 3);
+        assert_eq!(digit_count(255, 2), 8);
+        assert_eq!(digit_sum(-4096, 10), 19);
+        assert_eq!(reverse_digits(1200, 10), 21);
+        assert_eq!(reverse_digits(-123, 10), -321);
+    }
+
+    #[test]
+    fn test_concat() {
+        assert_eq!(concat(13, 7), 137);
+        assert_eq!(concat(6_u64, 0), 60);
+        assert_eq!(concat(0, 42), 42);
+        assert_eq!(concat(-12, 34), -1234);
+    }
+}
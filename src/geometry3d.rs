@@ -0,0 +1,199 @@
+use num::rational::{BigRational, Ratio};
+use num::{BigInt, One, Signed, ToPrimitive, Zero};
+
+pub type Pos3 = (isize, isize, isize);
+
+/// A point moving in a straight line, e.g. a hailstone given as
+/// `19, 13, 30 @ -2, 1, -2`. At time `t` it is at `pos + t * vel`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Ray3 {
+    pub pos: Pos3,
+    pub vel: Pos3,
+}
+
+impl Ray3 {
+    pub fn new(pos: Pos3, vel: Pos3) -> Self {
+        Self { pos, vel }
+    }
+
+    pub fn at(&self, t: isize) -> Pos3 {
+        (
+            self.pos.0 + t * self.vel.0,
+            self.pos.1 + t * self.vel.1,
+            self.pos.2 + t * self.vel.2,
+        )
+    }
+
+    /// Where the paths of the two rays cross when projected onto the XY plane
+    /// (ignoring Z), provided both rays reach that point at some time `t >= 0`.
+    /// The rays need not be there at the same time. Returns `None` if the
+    /// projected paths are parallel.
+    pub fn intersect_xy(&self, other: &Ray3) -> Option<(Ratio<i128>, Ratio<i128>)> {
+        let (x1, y1) = (self.pos.0 as i128, self.pos.1 as i128);
+        let (dx1, dy1) = (self.vel.0 as i128, self.vel.1 as i128);
+        let (x2, y2) = (other.pos.0 as i128, other.pos.1 as i128);
+        let (dx2, dy2) = (other.vel.0 as i128, other.vel.1 as i128);
+
+        let denom = dx1 * dy2 - dy1 * dx2;
+        if denom == 0 {
+            return None;
+        }
+
+        let (wx, wy) = (x2 - x1, y2 - y1);
+        let t = Ratio::new(wx * dy2 - wy * dx2, denom);
+        let s = Ratio::new(wx * dy1 - wy * dx1, denom);
+        if t.is_negative() || s.is_negative() {
+            return None;
+        }
+
+        Some((
+            Ratio::from_integer(x1) + t * dx1,
+            Ratio::from_integer(y1) + t * dy1,
+        ))
+    }
+
+    /// Do the XY projections of the two rays cross inside the square test area
+    /// `lo..=hi` (on both axes)?
+    pub fn crosses_xy_within(&self, other: &Ray3, lo: isize, hi: isize) -> bool {
+        let area = Ratio::from_integer(lo as i128)..=Ratio::from_integer(hi as i128);
+        self.intersect_xy(other)
+            .is_some_and(|(x, y)| area.contains(&x) && area.contains(&y))
+    }
+
+    /// Counts the pairs of `rays` whose XY paths cross inside the test area.
+    pub fn count_crossings_xy_within(rays: &[Ray3], lo: isize, hi: isize) -> usize {
+        (0..rays.len())
+            .map(|i| {
+                rays[(i + 1)..]
+                    .iter()
+                    .filter(|other| rays[i].crosses_xy_within(other, lo, hi))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Finds the ray (with integer position and velocity) which collides with
+    /// every one of `rays` at some time.
+    ///
+    /// If the rock is at `P + tV` then for every hailstone `(p - P) × (v - V) = 0`.
+    /// The `P × V` term is shared by all hailstones, so subtracting the
+    /// equations for two hailstones leaves three linear equations. This solves
+    /// the resulting system with exact rational Gaussian elimination, so the
+    /// answer has no floating point drift. Returns `None` if there are not
+    /// enough independent rays or the answer is not integral.
+    pub fn find_colliding_ray(rays: &[Ray3]) -> Option<Ray3> {
+        let mut rows: Vec<Vec<BigRational>> = vec![];
+        for (a, b) in (1..rays.len()).map(|j| (&rays[0], &rays[j])).take(4) {
+            rows.extend(collision_equations(a, b));
+        }
+
+        let solution = solve_linear(rows, 6)?;
+        let int = |r: &BigRational| r.is_integer().then(|| r.to_integer().to_isize())?;
+        Some(Ray3::new(
+            (int(&solution[0])?, int(&solution[1])?, int(&solution[2])?),
+            (int(&solution[3])?, int(&solution[4])?, int(&solution[5])?),
+        ))
+    }
+}
+
+/// The three rows `[Px, Py, Pz, Vx, Vy, Vz | rhs]` for `P × dv + dp × V = c_a - c_b`
+/// where `dp = p_a - p_b`, `dv = v_a - v_b` and `c = p × v`.
+fn collision_equations(a: &Ray3, b: &Ray3) -> Vec<Vec<BigRational>> {
+    let big = |n: isize| BigRational::from_integer(BigInt::from(n));
+    let dp = (a.pos.0 - b.pos.0, a.pos.1 - b.pos.1, a.pos.2 - b.pos.2);
+    let dv = (a.vel.0 - b.vel.0, a.vel.1 - b.vel.1, a.vel.2 - b.vel.2);
+    let (ca, cb) = (cross(&a.pos, &a.vel), cross(&b.pos, &b.vel));
+
+    [
+        [0, dv.2, -dv.1, 0, -dp.2, dp.1],
+        [-dv.2, 0, dv.0, dp.2, 0, -dp.0],
+        [dv.1, -dv.0, 0, -dp.1, dp.0, 0],
+    ]
+    .into_iter()
+    .zip([&ca.0 - &cb.0, &ca.1 - &cb.1, &ca.2 - &cb.2])
+    .map(|(coeffs, rhs)| {
+        coeffs
+            .into_iter()
+            .map(big)
+            .chain(std::iter::once(BigRational::from_integer(rhs)))
+            .collect()
+    })
+    .collect()
+}
+
+fn cross(a: &Pos3, b: &Pos3) -> (BigInt, BigInt, BigInt) {
+    let [ax, ay, az, bx, by, bz] = [a.0, a.1, a.2, b.0, b.1, b.2].map(BigInt::from);
+    (
+        &ay * &bz - &az * &by,
+        &az * &bx - &ax * &bz,
+        &ax * &by - &ay * &bx,
+    )
+}
+
+/// Solves an augmented (possibly over-determined) system for `n` unknowns.
+/// Returns `None` if the system is singular or inconsistent.
+fn solve_linear(mut rows: Vec<Vec<BigRational>>, n: usize) -> Option<Vec<BigRational>> {
+    for col in 0..n {
+        let pivot = (col..rows.len()).find(|&r| !rows[r][col].is_zero())?;
+        rows.swap(col, pivot);
+
+        let inv = BigRational::one() / &rows[col][col];
+        rows[col].iter_mut().for_each(|v| *v *= &inv);
+
+        let pivot_row = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != col && !row[col].is_zero() {
+                let factor = row[col].clone();
+                for (v, p) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *v -= &factor * p;
+                }
+            }
+        }
+    }
+
+    if rows[n..].iter().any(|row| !row[n].is_zero()) {
+        return None;
+    }
+    Some(rows.into_iter().take(n).map(|row| row[n].clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hailstones() -> Vec<Ray3> {
+        vec![
+            Ray3::new((19, 13, 30), (-2, 1, -2)),
+            Ray3::new((18, 19, 22), (-1, -1, -2)),
+            Ray3::new((20, 25, 34), (-2, -2, -4)),
+            Ray3::new((12, 31, 28), (-1, -2, -1)),
+            Ray3::new((20, 19, 15), (1, -5, -3)),
+        ]
+    }
+
+    #[test]
+    fn test_intersect_xy() {
+        let h = hailstones();
+        assert_eq!(
+            h[0].intersect_xy(&h[1]),
+            Some((Ratio::new(43, 3), Ratio::new(46, 3)))
+        );
+        // Parallel
+        assert_eq!(h[1].intersect_xy(&h[2]), None);
+        // Crossed in the past
+        assert_eq!(h[0].intersect_xy(&h[4]), None);
+    }
+
+    #[test]
+    fn test_count_crossings_xy_within() {
+        assert_eq!(Ray3::count_crossings_xy_within(&hailstones(), 7, 27), 2);
+    }
+
+    #[test]
+    fn test_find_colliding_ray() {
+        let h = hailstones();
+        let rock = Ray3::find_colliding_ray(&h).unwrap();
+        assert_eq!(rock, Ray3::new((24, 13, 10), (-3, 1, 2)));
+        assert_eq!(rock.at(5), h[0].at(5));
+    }
+}
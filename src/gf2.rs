@@ -0,0 +1,443 @@
+use crate::{BitGrid, gray_subsets};
+use std::ops::BitXorAssign;
+
+/// A vector over GF(2), packed 64 bits to a word.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gf2Vector {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl Gf2Vector {
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    /// E.g. from `bytes.bits()`, or a `Vec<bool>`.
+    pub fn from_bits<I: IntoIterator<Item = bool>>(bits: I) -> Self {
+        let bits: Vec<bool> = bits.into_iter().collect();
+        let mut v = Self::zeros(bits.len());
+        for (i, _) in bits.iter().enumerate().filter(|(_, b)| **b) {
+            v.set(i, true);
+        }
+        v
+    }
+
+    /// A vector of length `len` with the given positions set. A position
+    /// given more than once is still set.
+    pub fn from_indices<I: IntoIterator<Item = usize>>(len: usize, indices: I) -> Self {
+        let mut v = Self::zeros(len);
+        for i in indices {
+            v.set(i, true);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "Index {i} out of bounds");
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn set(&mut self, i: usize, on: bool) {
+        assert!(i < self.len, "Index {i} out of bounds");
+        if on {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn toggle(&mut self, i: usize) {
+        assert!(i < self.len, "Index {i} out of bounds");
+        self.words[i / 64] ^= 1 << (i % 64);
+    }
+
+    /// The Hamming weight.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Positions of the set bits, in ascending order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.get(i))
+    }
+
+    /// Dot product mod 2.
+    pub fn dot(&self, other: &Gf2Vector) -> bool {
+        let ones: u32 = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+}
+
+impl BitXorAssign<&Gf2Vector> for Gf2Vector {
+    fn bitxor_assign(&mut self, rhs: &Gf2Vector) {
+        assert_eq!(self.len, rhs.len, "Vector lengths differ");
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a ^= b;
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A matrix over GF(2), stored as bit-packed rows.
+///
+/// Lights out style puzzles ("each button toggles these lights, which buttons
+/// give this pattern?") are the system `A x = b`, with a column of `A` per
+/// button and a row per light. See `solve`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gf2Matrix {
+    cols: usize,
+    rows: Vec<Gf2Vector>,
+}
+
+impl Gf2Matrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            cols,
+            rows: vec![Gf2Vector::zeros(cols); rows],
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Gf2Vector>) -> Self {
+        let cols = rows.first().map_or(0, Gf2Vector::len);
+        assert!(rows.iter().all(|r| r.len() == cols), "Row lengths differ");
+        Self { cols, rows }
+    }
+
+    /// Builds a matrix from its columns, each given as the rows it has set.
+    /// E.g. the lights toggled by each button.
+    pub fn from_columns<C, I>(rows: usize, columns: C) -> Self
+    where
+        C: IntoIterator<Item = I>,
+        I: IntoIterator<Item = usize>,
+    {
+        let columns: Vec<Vec<usize>> = columns
+            .into_iter()
+            .map(|c| c.into_iter().collect())
+            .collect();
+        let mut matrix = Self::new(rows, columns.len());
+        for (c, column) in columns.iter().enumerate() {
+            for &r in column {
+                matrix.toggle(r, c);
+            }
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, r: usize) -> &Gf2Vector {
+        &self.rows[r]
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        self.rows[r].get(c)
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, on: bool) {
+        self.rows[r].set(c, on);
+    }
+
+    pub fn toggle(&mut self, r: usize, c: usize) {
+        self.rows[r].toggle(c);
+    }
+
+    /// `A x`
+    pub fn mul_vector(&self, x: &Gf2Vector) -> Gf2Vector {
+        Gf2Vector::from_bits(self.rows.iter().map(|row| row.dot(x)))
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().reduce(None).len()
+    }
+
+    /// Reduces the matrix to reduced row echelon form in place, applying the
+    /// same row operations to `rhs` if given. Returns the pivot column of
+    /// each of the leading (non-zero) rows.
+    pub fn reduce(&mut self, mut rhs: Option<&mut Gf2Vector>) -> Vec<usize> {
+        let mut pivots = vec![];
+        for col in 0..self.cols {
+            let r = pivots.len();
+            let Some(p) = (r..self.rows.len()).find(|&i| self.rows[i].get(col)) else {
+                continue;
+            };
+            self.rows.swap(r, p);
+            if let Some(rhs) = rhs.as_deref_mut() {
+                let (a, b) = (rhs.get(r), rhs.get(p));
+                rhs.set(r, b);
+                rhs.set(p, a);
+            }
+
+            let pivot_row = self.rows[r].clone();
+            let pivot_rhs = rhs.as_deref().is_some_and(|rhs| rhs.get(r));
+            for i in (0..self.rows.len()).filter(|&i| i != r) {
+                if self.rows[i].get(col) {
+                    self.rows[i] ^= &pivot_row;
+                    if pivot_rhs && let Some(rhs) = rhs.as_deref_mut() {
+                        rhs.toggle(i);
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    /// A basis for the solutions of `A x = 0`.
+    pub fn null_space(&self) -> Vec<Gf2Vector> {
+        let mut reduced = self.clone();
+        let pivots = reduced.reduce(None);
+        null_space(&reduced, &pivots)
+    }
+
+    /// Every solution of `A x = target`, or `None` if there are none.
+    pub fn solve(&self, target: &Gf2Vector) -> Option<Gf2Solutions> {
+        assert_eq!(target.len(), self.rows(), "Target length must match rows");
+        let mut reduced = self.clone();
+        let mut rhs = target.clone();
+        let pivots = reduced.reduce(Some(&mut rhs));
+        if (pivots.len()..self.rows()).any(|r| rhs.get(r)) {
+            return None;
+        }
+
+        let mut particular = Gf2Vector::zeros(self.cols);
+        for (r, &p) in pivots.iter().enumerate() {
+            particular.set(p, rhs.get(r));
+        }
+        Some(Gf2Solutions {
+            particular,
+            null_space: null_space(&reduced, &pivots),
+        })
+    }
+}
+
+/// One basis vector per free column, from a matrix in reduced row echelon form.
+fn null_space(reduced: &Gf2Matrix, pivots: &[usize]) -> Vec<Gf2Vector> {
+    (0..reduced.cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut v = Gf2Vector::zeros(reduced.cols);
+            v.set(free, true);
+            for (r, &p) in pivots.iter().enumerate() {
+                if reduced.get(r, free) {
+                    v.set(p, true);
+                }
+            }
+            v
+        })
+        .collect()
+}
+
+impl From<&BitGrid> for Gf2Matrix {
+    fn from(grid: &BitGrid) -> Self {
+        let rows = (0..grid.height())
+            .map(|y| {
+                Gf2Vector::from_bits((0..grid.width()).map(|x| grid.get(&(x as isize, y as isize))))
+            })
+            .collect();
+        Self::from_rows(rows)
+    }
+}
+
+/// The most null space dimensions `Gf2Solutions::iter` and `min_weight`
+/// enumerate, i.e. about 16 million solutions.
+pub const MAX_ENUMERATED_NULL_SPACE: usize = 24;
+
+/// The solutions of `A x = b`: a particular solution plus any combination of
+/// the null space basis.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gf2Solutions {
+    particular: Gf2Vector,
+    null_space: Vec<Gf2Vector>,
+}
+
+impl Gf2Solutions {
+    pub fn particular(&self) -> &Gf2Vector {
+        &self.particular
+    }
+
+    pub fn null_space(&self) -> &[Gf2Vector] {
+        &self.null_space
+    }
+
+    /// `2^dim(null space)`, or `None` if that does not fit in a `u128`
+    /// (a null space of 128 or more dimensions).
+    pub fn count(&self) -> Option<u128> {
+        1u128.checked_shl(self.null_space.len() as u32)
+    }
+
+    /// Every solution, stepping between them with a single XOR each (Gray
+    /// code order). There are `count()` of them, so this is `None` for a null
+    /// space of more than `MAX_ENUMERATED_NULL_SPACE` dimensions.
+    pub fn iter(&self) -> Option<impl Iterator<Item = Gf2Vector> + '_> {
+        if self.null_space.len() > MAX_ENUMERATED_NULL_SPACE {
+            return None;
+        }
+
+        let mut current = self.particular.clone();
+        Some(
+            gray_subsets(self.null_space.len()).map(move |(_, flipped)| {
+                if let Some(i) = flipped {
+                    current ^= &self.null_space[i];
+                }
+                current.clone()
+            }),
+        )
+    }
+
+    /// A solution with the fewest ones, i.e. the fewest button presses.
+    /// Found by trying every solution, so `None` when `iter` is.
+    pub fn min_weight(&self) -> Option<Gf2Vector> {
+        self.iter().map(|solutions| {
+            solutions
+                .min_by_key(Gf2Vector::count_ones)
+                .expect("There is always at least one solution")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pressing a light toggles it and its orthogonal neighbours.
+    fn lights_out(size: usize) -> Gf2Matrix {
+        let index = |x: usize, y: usize| y * size + x;
+        let columns = (0..size * size).map(|i| {
+            let (x, y) = (i % size, i / size);
+            let mut toggled = vec![i];
+            if x > 0 {
+                toggled.push(index(x - 1, y));
+            }
+            if x + 1 < size {
+                toggled.push(index(x + 1, y));
+            }
+            if y > 0 {
+                toggled.push(index(x, y - 1));
+            }
+            if y + 1 < size {
+                toggled.push(index(x, y + 1));
+            }
+            toggled
+        });
+        Gf2Matrix::from_columns(size * size, columns)
+    }
+
+    #[test]
+    fn test_vector() {
+        let mut v = Gf2Vector::from_bits([true, false, true]);
+        assert_eq!(v.ones().collect::<Vec<_>>(), vec![0, 2]);
+        v ^= &Gf2Vector::from_indices(3, [0, 1]);
+        assert_eq!(v, Gf2Vector::from_indices(3, [1, 2]));
+        assert!(v.dot(&Gf2Vector::from_indices(3, [2])));
+
+        assert_eq!(
+            Gf2Vector::from_indices(3, [2, 0, 2]),
+            Gf2Vector::from_bits([true, false, true])
+        );
+
+        let long = Gf2Vector::from_indices(130, [0, 64, 129]);
+        assert_eq!(long.count_ones(), 3);
+        assert!(long.get(129));
+    }
+
+    #[test]
+    fn test_lights_out() {
+        let a = lights_out(3);
+        assert_eq!(a.rank(), 9);
+        let all_on = Gf2Vector::from_bits([true; 9]);
+        let solutions = a.solve(&all_on).unwrap();
+        assert_eq!(solutions.count(), Some(1));
+        let presses = solutions.min_weight().unwrap();
+        assert_eq!(presses.ones().collect::<Vec<_>>(), vec![0, 2, 4, 6, 8]);
+        assert_eq!(a.mul_vector(&presses), all_on);
+
+        // The 5x5 game has a two dimensional null space, so not every
+        // pattern can be made and those that can have 4 solutions.
+        let a = lights_out(5);
+        assert_eq!(a.rank(), 23);
+        let null_space = a.null_space();
+        assert_eq!(null_space.len(), 2);
+        assert!(null_space.iter().all(|v| a.mul_vector(v).is_zero()));
+        assert!(a.solve(&Gf2Vector::from_indices(25, [0])).is_none());
+        let solutions = a.solve(&Gf2Vector::from_bits([true; 25])).unwrap();
+        assert_eq!(solutions.count(), Some(4));
+        assert_eq!(solutions.iter().unwrap().count(), 4);
+        assert_eq!(solutions.min_weight().unwrap().count_ones(), 15);
+    }
+
+    #[test]
+    fn test_count_overflow() {
+        let free = |cols: usize| Gf2Matrix::new(1, cols).solve(&Gf2Vector::zeros(1)).unwrap();
+        assert_eq!(free(127).count(), Some(1 << 127));
+        assert_eq!(free(128).count(), None);
+        assert_eq!(free(200).count(), None);
+    }
+
+    #[test]
+    fn test_large_null_space() {
+        let free = |cols: usize| Gf2Matrix::new(1, cols).solve(&Gf2Vector::zeros(1)).unwrap();
+        let small = free(MAX_ENUMERATED_NULL_SPACE - 8);
+        assert_eq!(small.iter().unwrap().count(), 1 << 16);
+        assert!(small.min_weight().unwrap().is_zero());
+
+        for cols in [MAX_ENUMERATED_NULL_SPACE + 1, 65, 200] {
+            assert!(free(cols).iter().is_none());
+            assert_eq!(free(cols).min_weight(), None);
+        }
+    }
+
+    #[test]
+    fn test_machine_buttons() {
+        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1)
+        let buttons = [
+            vec![3],
+            vec![1, 3],
+            vec![2],
+            vec![2, 3],
+            vec![0, 2],
+            vec![0, 1],
+        ];
+        let a = Gf2Matrix::from_columns(4, buttons);
+        let target = Gf2Vector::from_bits([false, true, true, false]);
+        let presses = a.solve(&target).unwrap().min_weight().unwrap();
+        assert_eq!(presses.count_ones(), 2);
+        assert_eq!(a.mul_vector(&presses), target);
+    }
+
+    #[test]
+    fn test_from_bit_grid() {
+        let grid = BitGrid::parse("#.#\n.##", '#');
+        let a = Gf2Matrix::from(&grid);
+        assert_eq!((a.rows(), a.cols()), (2, 3));
+        assert!(a.get(1, 2) && !a.get(1, 0));
+        assert_eq!(a.rank(), 2);
+    }
+}
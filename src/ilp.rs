@@ -0,0 +1,735 @@
+use num::Integer;
+use num::rational::Ratio;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::{Bound, RangeBounds};
+use z3::ast::Int;
+use z3::{Optimize, SatResult};
+
+/// A small integer linear program: integer variables with optional bounds,
+/// linear constraints and an optional objective.
+///
+/// ```
+/// let mut ilp = Ilp::new();
+/// let x = ilp.var(0..);
+/// let y = ilp.var(0..);
+/// ilp.eq([(x, 1), (y, 1)], 10).eq([(x, 1), (y, -1)], 4);
+/// ilp.minimise([(x, 2), (y, 1)]);
+/// let solution = ilp.solve().unwrap();
+/// assert_eq!((solution.value(x), solution.value(y)), (7, 3));
+/// ```
+///
+/// `solve` uses z3. `solve_with(IlpBackend::Search)` instead solves the
+/// equalities by Gaussian elimination and then searches the free variables
+/// within their bounds, which avoids z3's start-up cost for small systems.
+/// Bounds are tightened from the constraints first (e.g. non-negative
+/// variables summing to 10 are each at most 10), so these don't all need to
+/// be given explicitly.
+#[derive(Clone, Debug, Default)]
+pub struct Ilp {
+    bounds: Vec<(Option<i64>, Option<i64>)>,
+    constraints: Vec<Constraint>,
+    objective: Vec<(usize, i64)>,
+    maximise: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct IlpVar(usize);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IlpBackend {
+    #[default]
+    Z3,
+    Search,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IlpSolution {
+    values: Vec<i64>,
+    objective: i64,
+}
+
+impl IlpSolution {
+    pub fn value(&self, var: IlpVar) -> i64 {
+        self.values[var.0]
+    }
+
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    /// The objective's value (`0` if there was no objective).
+    pub fn objective(&self) -> i64 {
+        self.objective
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum IlpError {
+    Infeasible,
+    /// The search backend couldn't find finite bounds for a free variable.
+    Unbounded(IlpVar),
+    Unknown,
+}
+
+impl Error for IlpError {}
+
+impl Display for IlpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IlpError::Infeasible => write!(f, "no integer solution satisfies the constraints."),
+            IlpError::Unbounded(var) => {
+                write!(f, "variable {} needs bounds for the search backend.", var.0)
+            }
+            IlpError::Unknown => write!(f, "the solver gave up."),
+        }
+    }
+}
+
+/// Optional lower and upper bounds of a variable.
+type Bounds = (Option<i128>, Option<i128>);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Relation {
+    Eq,
+    Le,
+    Ge,
+}
+
+#[derive(Clone, Debug)]
+struct Constraint {
+    terms: Vec<(usize, i64)>,
+    relation: Relation,
+    rhs: i64,
+}
+
+impl Ilp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an integer variable, e.g. `ilp.var(0..)` or `ilp.var(-5..=5)`.
+    pub fn var(&mut self, bounds: impl RangeBounds<i64>) -> IlpVar {
+        let lo = match bounds.start_bound() {
+            Bound::Included(&lo) => Some(lo),
+            Bound::Excluded(&lo) => Some(lo + 1),
+            Bound::Unbounded => None,
+        };
+        let hi = match bounds.end_bound() {
+            Bound::Included(&hi) => Some(hi),
+            Bound::Excluded(&hi) => Some(hi - 1),
+            Bound::Unbounded => None,
+        };
+        self.bounds.push((lo, hi));
+        IlpVar(self.bounds.len() - 1)
+    }
+
+    pub fn vars(&mut self, n: usize, bounds: impl RangeBounds<i64> + Clone) -> Vec<IlpVar> {
+        (0..n).map(|_| self.var(bounds.clone())).collect()
+    }
+
+    /// `Σ coefficient * var = rhs`
+    pub fn eq(&mut self, terms: impl IntoIterator<Item = (IlpVar, i64)>, rhs: i64) -> &mut Self {
+        self.constrain(terms, Relation::Eq, rhs)
+    }
+
+    /// `Σ coefficient * var <= rhs`
+    pub fn le(&mut self, terms: impl IntoIterator<Item = (IlpVar, i64)>, rhs: i64) -> &mut Self {
+        self.constrain(terms, Relation::Le, rhs)
+    }
+
+    /// `Σ coefficient * var >= rhs`
+    pub fn ge(&mut self, terms: impl IntoIterator<Item = (IlpVar, i64)>, rhs: i64) -> &mut Self {
+        self.constrain(terms, Relation::Ge, rhs)
+    }
+
+    pub fn minimise(&mut self, terms: impl IntoIterator<Item = (IlpVar, i64)>) -> &mut Self {
+        self.objective = terms.into_iter().map(|(v, c)| (v.0, c)).collect();
+        self.maximise = false;
+        self
+    }
+
+    pub fn maximise(&mut self, terms: impl IntoIterator<Item = (IlpVar, i64)>) -> &mut Self {
+        self.minimise(terms);
+        self.maximise = true;
+        self
+    }
+
+    pub fn solve(&self) -> Result<IlpSolution, IlpError> {
+        self.solve_with(IlpBackend::default())
+    }
+
+    pub fn solve_with(&self, backend: IlpBackend) -> Result<IlpSolution, IlpError> {
+        let values = match backend {
+            IlpBackend::Z3 => self.solve_z3()?,
+            IlpBackend::Search => self.solve_search()?,
+        };
+        let objective = self.objective.iter().map(|&(i, c)| c * values[i]).sum();
+        Ok(IlpSolution { values, objective })
+    }
+
+    fn constrain(
+        &mut self,
+        terms: impl IntoIterator<Item = (IlpVar, i64)>,
+        relation: Relation,
+        rhs: i64,
+    ) -> &mut Self {
+        let terms = terms.into_iter().map(|(v, c)| (v.0, c)).collect();
+        self.constraints.push(Constraint {
+            terms,
+            relation,
+            rhs,
+        });
+        self
+    }
+
+    /* ------------------------------------------------------------------ */
+
+    fn solve_z3(&self) -> Result<Vec<i64>, IlpError> {
+        let opt = Optimize::new();
+
+        let vars: Vec<_> = (0..self.bounds.len())
+            .map(|i| Int::new_const(format!("x{}", i)))
+            .collect();
+        for (var, &(lo, hi)) in vars.iter().zip(&self.bounds) {
+            if let Some(lo) = lo {
+                opt.assert(&var.ge(Int::from_i64(lo)));
+            }
+            if let Some(hi) = hi {
+                opt.assert(&var.le(Int::from_i64(hi)));
+            }
+        }
+
+        let sum = |terms: &[(usize, i64)]| {
+            let mut products = vec![Int::from_i64(0)];
+            products.extend(
+                terms
+                    .iter()
+                    .map(|&(i, c)| Int::mul(&[&Int::from_i64(c), &vars[i]])),
+            );
+            Int::add(&products)
+        };
+
+        for constraint in &self.constraints {
+            let lhs = sum(&constraint.terms);
+            let rhs = Int::from_i64(constraint.rhs);
+            match constraint.relation {
+                Relation::Eq => opt.assert(&lhs.eq(&rhs)),
+                Relation::Le => opt.assert(&lhs.le(&rhs)),
+                Relation::Ge => opt.assert(&lhs.ge(&rhs)),
+            }
+        }
+
+        if !self.objective.is_empty() {
+            let objective = sum(&self.objective);
+            if self.maximise {
+                opt.maximize(&objective);
+            } else {
+                opt.minimize(&objective);
+            }
+        }
+
+        match opt.check(&[]) {
+            SatResult::Sat => {
+                let model = opt.get_model().ok_or(IlpError::Unknown)?;
+                vars.iter()
+                    .map(|var| model.eval(var, true).and_then(|v| v.as_i64()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(IlpError::Unknown)
+            }
+            SatResult::Unsat => Err(IlpError::Infeasible),
+            SatResult::Unknown => Err(IlpError::Unknown),
+        }
+    }
+
+    /* ------------------------------------------------------------------ */
+
+    fn solve_search(&self) -> Result<Vec<i64>, IlpError> {
+        let n = self.bounds.len();
+        let bounds = self.tightened_bounds()?;
+
+        // Reduced row echelon form of the equalities: each pivot variable is
+        // then fixed by the free variables.
+        let mut rows: Vec<Vec<Ratio<i128>>> = self
+            .constraints
+            .iter()
+            .filter(|c| c.relation == Relation::Eq)
+            .map(|c| {
+                let mut row = vec![Ratio::from_integer(0); n + 1];
+                for &(i, coefficient) in &c.terms {
+                    row[i] += coefficient as i128;
+                }
+                row[n] = Ratio::from_integer(c.rhs as i128);
+                row
+            })
+            .collect();
+        let pivots = reduce(&mut rows, n);
+        if rows[pivots.len()..].iter().any(|row| row[n] != 0.into()) {
+            return Err(IlpError::Infeasible);
+        }
+
+        let mut free: Vec<usize> = (0..n).filter(|col| !pivots.contains(col)).collect();
+        if let Some(&f) = free
+            .iter()
+            .find(|&&f| bounds[f].0.is_none() || bounds[f].1.is_none())
+        {
+            return Err(IlpError::Unbounded(IlpVar(f)));
+        }
+        // Branching on the narrowest variables first prunes the most.
+        free.sort_by_key(|&f| bounds[f].1.unwrap() - bounds[f].0.unwrap());
+        let ranges: Vec<_> = free
+            .iter()
+            .map(|&f| (bounds[f].0.unwrap(), bounds[f].1.unwrap()))
+            .collect();
+
+        // Every variable as an affine function of the free variables.
+        let mut exprs = vec![Affine::zero(free.len()); n];
+        for (k, &f) in free.iter().enumerate() {
+            exprs[f].coefficients[k] = 1.into();
+        }
+        for (row, &p) in rows.iter().zip(&pivots) {
+            exprs[p].constant = row[n];
+            for (k, &f) in free.iter().enumerate() {
+                exprs[p].coefficients[k] = -row[f];
+            }
+        }
+        let combine = |terms: &[(usize, i64)]| {
+            let mut expr = Affine::zero(free.len());
+            for &(i, c) in terms {
+                expr.add_scaled(&exprs[i], c);
+            }
+            expr
+        };
+
+        let mut checks = vec![];
+        for &p in &pivots {
+            let (lo, hi) = bounds[p];
+            checks.push(Check::new(&exprs[p], Test::Integral(lo, hi)));
+        }
+        for constraint in &self.constraints {
+            let expr = combine(&constraint.terms);
+            let rhs = constraint.rhs as i128;
+            match constraint.relation {
+                Relation::Eq => {}
+                Relation::Le => checks.push(Check::new(&expr, Test::AtMost(rhs))),
+                Relation::Ge => checks.push(Check::new(&expr.negated(), Test::AtMost(-rhs))),
+            }
+        }
+
+        let mut objective = combine(&self.objective);
+        if self.maximise {
+            objective = objective.negated();
+        }
+        let mut search = Search {
+            ranges: &ranges,
+            checks,
+            objective: Check::new(&objective, Test::AtMost(0)),
+            values: vec![0; free.len()],
+            best: None,
+            stop_at_first: self.objective.is_empty(),
+        };
+        search.run(0);
+
+        let best = search.best.ok_or(IlpError::Infeasible)?;
+        Ok(exprs
+            .iter()
+            .map(|expr| expr.eval(&best.1).to_integer() as i64)
+            .collect())
+    }
+
+    /// Narrows each variable's bounds using the constraints, until nothing
+    /// changes (or enough passes have been made).
+    fn tightened_bounds(&self) -> Result<Vec<Bounds>, IlpError> {
+        let mut bounds: Vec<_> = self
+            .bounds
+            .iter()
+            .map(|&(lo, hi)| (lo.map(i128::from), hi.map(i128::from)))
+            .collect();
+
+        // Everything as `Σ a x <= b`.
+        let mut upper = vec![];
+        for c in &self.constraints {
+            let terms: Vec<_> = c.terms.iter().map(|&(i, a)| (i, a as i128)).collect();
+            let negated = terms.iter().map(|&(i, a)| (i, -a)).collect();
+            let rhs = c.rhs as i128;
+            match c.relation {
+                Relation::Le => upper.push((terms, rhs)),
+                Relation::Ge => upper.push((negated, -rhs)),
+                Relation::Eq => {
+                    upper.push((terms, rhs));
+                    upper.push((negated, -rhs));
+                }
+            }
+        }
+
+        for _ in 0..10 {
+            let mut changed = false;
+            for (terms, rhs) in &upper {
+                // Smallest possible value of each term.
+                let mins: Vec<Option<i128>> = terms
+                    .iter()
+                    .map(|&(i, a)| match a.cmp(&0) {
+                        std::cmp::Ordering::Greater => bounds[i].0.map(|lo| a * lo),
+                        std::cmp::Ordering::Less => bounds[i].1.map(|hi| a * hi),
+                        std::cmp::Ordering::Equal => Some(0),
+                    })
+                    .collect();
+                for (j, &(i, a)) in terms.iter().enumerate() {
+                    if a == 0 {
+                        continue;
+                    }
+                    let others = mins
+                        .iter()
+                        .enumerate()
+                        .filter(|&(k, _)| k != j)
+                        .try_fold(0, |acc, (_, m)| m.map(|m| acc + m));
+                    let Some(others) = others else { continue };
+                    let limit = rhs - others;
+                    let (lo, hi) = &mut bounds[i];
+                    if a > 0 {
+                        let new = Integer::div_floor(&limit, &a);
+                        if hi.is_none_or(|hi| new < hi) {
+                            *hi = Some(new);
+                            changed = true;
+                        }
+                    } else {
+                        let new = Integer::div_ceil(&limit, &a);
+                        if lo.is_none_or(|lo| new > lo) {
+                            *lo = Some(new);
+                            changed = true;
+                        }
+                    }
+                    if let (Some(lo), Some(hi)) = (*lo, *hi)
+                        && lo > hi
+                    {
+                        return Err(IlpError::Infeasible);
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        Ok(bounds)
+    }
+}
+
+/// Reduces `rows` (with the right hand side in column `n`) to reduced row
+/// echelon form, returning the pivot column of each leading row.
+fn reduce(rows: &mut [Vec<Ratio<i128>>], n: usize) -> Vec<usize> {
+    let mut pivots = vec![];
+    for col in 0..n {
+        let r = pivots.len();
+        let Some(p) = (r..rows.len()).find(|&i| rows[i][col] != 0.into()) else {
+            continue;
+        };
+        rows.swap(r, p);
+        let lead = rows[r][col];
+        rows[r].iter_mut().for_each(|v| *v /= lead);
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if i != r && factor != 0.into() {
+                for (v, p) in row.iter_mut().zip(&pivot_row) {
+                    *v -= factor * p;
+                }
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+#[derive(Clone, Debug)]
+struct Affine {
+    constant: Ratio<i128>,
+    coefficients: Vec<Ratio<i128>>,
+}
+
+impl Affine {
+    fn zero(n: usize) -> Self {
+        Self {
+            constant: 0.into(),
+            coefficients: vec![0.into(); n],
+        }
+    }
+
+    fn add_scaled(&mut self, other: &Affine, scale: i64) {
+        let scale = Ratio::from_integer(scale as i128);
+        self.constant += other.constant * scale;
+        for (a, b) in self.coefficients.iter_mut().zip(&other.coefficients) {
+            *a += b * scale;
+        }
+    }
+
+    fn negated(&self) -> Self {
+        Self {
+            constant: -self.constant,
+            coefficients: self.coefficients.iter().map(|c| -c).collect(),
+        }
+    }
+
+    fn eval(&self, values: &[i128]) -> Ratio<i128> {
+        self.coefficients
+            .iter()
+            .zip(values)
+            .fold(self.constant, |acc, (c, &v)| acc + c * v)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Test {
+    /// An integer within the (optional) bounds.
+    Integral(Option<i128>, Option<i128>),
+    AtMost(i128),
+}
+
+/// An affine expression scaled to integer coefficients, `(constant + Σ c x)
+/// / denominator`, and the test it must pass. It is checked as soon as the
+/// last free variable it depends on has been chosen.
+struct Check {
+    constant: i128,
+    coefficients: Vec<i128>,
+    denominator: i128,
+    depth: usize,
+    test: Test,
+}
+
+impl Check {
+    fn new(expr: &Affine, test: Test) -> Self {
+        let denominator = expr
+            .coefficients
+            .iter()
+            .fold(*expr.constant.denom(), |acc, c| acc.lcm(c.denom()));
+        let scale = |r: &Ratio<i128>| (r * denominator).to_integer();
+        let coefficients: Vec<i128> = expr.coefficients.iter().map(scale).collect();
+        let depth = coefficients
+            .iter()
+            .rposition(|&c| c != 0)
+            .map_or(0, |k| k + 1);
+        Self {
+            constant: scale(&expr.constant),
+            coefficients,
+            denominator,
+            depth,
+            test,
+        }
+    }
+
+    fn numerator(&self, values: &[i128]) -> i128 {
+        self.coefficients
+            .iter()
+            .zip(values)
+            .fold(self.constant, |acc, (c, v)| acc + c * v)
+    }
+
+    fn passes(&self, values: &[i128]) -> bool {
+        let numerator = self.numerator(values);
+        match self.test {
+            Test::Integral(lo, hi) => {
+                if numerator % self.denominator != 0 {
+                    return false;
+                }
+                let v = numerator / self.denominator;
+                lo.is_none_or(|lo| v >= lo) && hi.is_none_or(|hi| v <= hi)
+            }
+            Test::AtMost(limit) => numerator <= limit * self.denominator,
+        }
+    }
+}
+
+/// Depth first search over the free variables, minimising `objective`.
+struct Search<'a> {
+    ranges: &'a [(i128, i128)],
+    checks: Vec<Check>,
+    objective: Check,
+    values: Vec<i128>,
+    best: Option<(i128, Vec<i128>)>,
+    stop_at_first: bool,
+}
+
+impl Search<'_> {
+    fn run(&mut self, depth: usize) {
+        if self.stop_at_first && self.best.is_some() {
+            return;
+        }
+        if !self
+            .checks
+            .iter()
+            .all(|c| c.depth != depth || c.passes(&self.values))
+        {
+            return;
+        }
+        if depth == self.values.len() {
+            let objective = self.objective.numerator(&self.values);
+            if self.best.as_ref().is_none_or(|(best, _)| objective < *best) {
+                self.best = Some((objective, self.values.clone()));
+            }
+            return;
+        }
+
+        let Some((lo, hi)) = self.narrowed(depth) else {
+            return;
+        };
+        for v in lo..=hi {
+            self.values[depth] = v;
+            self.run(depth + 1);
+        }
+    }
+
+    /// The values of free variable `depth` which could still satisfy every
+    /// check (and beat the best objective so far), given the values already
+    /// chosen and the ranges of the variables still to come.
+    fn narrowed(&self, depth: usize) -> Option<(i128, i128)> {
+        let (mut lo, mut hi) = self.ranges[depth];
+        let objective = self
+            .best
+            .as_ref()
+            .map(|(best, _)| (&self.objective, best - 1));
+        let checks = self.checks.iter().map(|check| {
+            let limits = match check.test {
+                Test::Integral(lo, hi) => (
+                    lo.map(|lo| lo * check.denominator),
+                    hi.map(|hi| hi * check.denominator),
+                ),
+                Test::AtMost(limit) => (None, Some(limit * check.denominator)),
+            };
+            (check, limits)
+        });
+        let objective = objective.map(|(check, best)| (check, (None, Some(best))));
+
+        for (check, (min, max)) in checks.chain(objective) {
+            let c = check.coefficients[depth];
+            if c == 0 {
+                continue;
+            }
+            let chosen = check.constant
+                + (0..depth)
+                    .map(|k| check.coefficients[k] * self.values[k])
+                    .sum::<i128>();
+            let (rest_min, rest_max) = (depth + 1..self.values.len())
+                .map(|k| {
+                    let (a, b) = (
+                        check.coefficients[k] * self.ranges[k].0,
+                        check.coefficients[k] * self.ranges[k].1,
+                    );
+                    (a.min(b), a.max(b))
+                })
+                .fold((0, 0), |(x, y), (a, b)| (x + a, y + b));
+
+            // min <= chosen + c * v + rest <= max
+            let at_least = min.map(|min| min - chosen - rest_max);
+            let at_most = max.map(|max| max - chosen - rest_min);
+            if c > 0 {
+                if let Some(n) = at_least {
+                    lo = lo.max(Integer::div_ceil(&n, &c));
+                }
+                if let Some(n) = at_most {
+                    hi = hi.min(Integer::div_floor(&n, &c));
+                }
+            } else {
+                if let Some(n) = at_least {
+                    hi = hi.min(Integer::div_floor(&n, &c));
+                }
+                if let Some(n) = at_most {
+                    lo = lo.max(Integer::div_ceil(&n, &c));
+                }
+            }
+            if lo > hi {
+                return None;
+            }
+        }
+        Some((lo, hi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Day 10 style: fewest button presses to reach the joltage targets.
+    fn presses(buttons: &[&[usize]], targets: &[i64], backend: IlpBackend) -> i64 {
+        let mut ilp = Ilp::new();
+        let presses = ilp.vars(buttons.len(), 0..);
+        for (counter, &target) in targets.iter().enumerate() {
+            let terms = buttons
+                .iter()
+                .zip(&presses)
+                .filter(|(wiring, _)| wiring.contains(&counter))
+                .map(|(_, &var)| (var, 1));
+            ilp.eq(terms, target);
+        }
+        ilp.minimise(presses.iter().map(|&var| (var, 1)));
+        ilp.solve_with(backend).unwrap().objective()
+    }
+
+    fn check_machines(backend: IlpBackend) {
+        let buttons: &[&[usize]] = &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]];
+        assert_eq!(presses(buttons, &[3, 5, 4, 7], backend), 10);
+        let buttons: &[&[usize]] = &[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]];
+        assert_eq!(presses(buttons, &[7, 5, 12, 7, 2], backend), 12);
+        let buttons: &[&[usize]] = &[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]];
+        assert_eq!(presses(buttons, &[10, 11, 11, 5, 10, 5], backend), 11);
+    }
+
+    #[test]
+    fn test_search_machines() {
+        check_machines(IlpBackend::Search);
+    }
+
+    #[test]
+    fn test_z3_machines() {
+        check_machines(IlpBackend::Z3);
+    }
+
+    #[test]
+    fn test_search_inequalities() {
+        // Maximise 3x + 2y with x + y <= 4, x + 3y <= 6, x <= 3
+        let mut ilp = Ilp::new();
+        let x = ilp.var(0..=3);
+        let y = ilp.var(0..);
+        ilp.le([(x, 1), (y, 1)], 4).le([(x, 1), (y, 3)], 6);
+        ilp.maximise([(x, 3), (y, 2)]);
+        let solution = ilp.solve_with(IlpBackend::Search).unwrap();
+        assert_eq!(solution.values(), &[3, 1]);
+        assert_eq!(solution.objective(), 11);
+    }
+
+    #[test]
+    fn test_search_negative_and_rational() {
+        // 2x = 3y + 1 with x, y in -10..=10, minimise x
+        let mut ilp = Ilp::new();
+        let x = ilp.var(-10..=10);
+        let y = ilp.var(-10..=10);
+        ilp.eq([(x, 2), (y, -3)], 1).minimise([(x, 1)]);
+        let solution = ilp.solve_with(IlpBackend::Search).unwrap();
+        assert_eq!((solution.value(x), solution.value(y)), (-10, -7));
+    }
+
+    #[test]
+    fn test_search_errors() {
+        let mut ilp = Ilp::new();
+        let x = ilp.var(0..);
+        ilp.eq([(x, 2)], 3);
+        assert_eq!(
+            ilp.solve_with(IlpBackend::Search),
+            Err(IlpError::Infeasible)
+        );
+
+        let mut ilp = Ilp::new();
+        let x = ilp.var(0..);
+        let y = ilp.var(..);
+        ilp.ge([(x, 1), (y, 1)], 3);
+        assert_eq!(
+            ilp.solve_with(IlpBackend::Search),
+            Err(IlpError::Unbounded(x))
+        );
+
+        // Feasibility only: any solution will do
+        let mut ilp = Ilp::new();
+        let xs = ilp.vars(3, 0..=5);
+        ilp.eq(xs.iter().map(|&x| (x, 1)), 12);
+        let solution = ilp.solve_with(IlpBackend::Search).unwrap();
+        assert_eq!(solution.values().iter().sum::<i64>(), 12);
+    }
+}
@@ -13,42 +13,51 @@ use std::fmt::Debug;
 use std::fmt::Display;
 
 mod allocations;
+mod digest;
 mod digits;
 mod direction;
+mod geometry3d;
+mod gf2;
 mod graph;
 mod grid;
+mod ilp;
 mod line_segment;
 mod maps;
 mod md5;
 mod nibble;
+mod number;
+mod polynomial;
+mod repunit;
+mod spelled;
 
 pub use allocations::*;
 #[allow(unused_imports)]
 pub use aoc_parse::{ParseIter, parser, prelude::*};
+pub use digest::*;
 pub use digits::*;
 pub use direction::*;
+pub use geometry3d::*;
+pub use gf2::*;
 pub use graph::*;
 pub use grid::*;
+pub use ilp::*;
 pub use line_segment::*;
 pub use maps::*;
 pub use md5::*;
 pub use nibble::*;
+pub use number::*;
+pub use polynomial::*;
+pub use repunit::*;
+pub use spelled::*;
 
+/// The value of an exact digit name, "zero" to "nine". See `digit_scanner`
+/// for finding digit names inside longer strings.
 #[allow(dead_code)]
 pub fn name_to_digit(s: &str) -> Option<u32> {
-    match s {
-        "zero" => Some(0),
-        "one" => Some(1),
-        "two" => Some(2),
-        "three" => Some(3),
-        "four" => Some(4),
-        "five" => Some(5),
-        "six" => Some(6),
-        "seven" => Some(7),
-        "eight" => Some(8),
-        "nine" => Some(9),
-        _ => None,
-    }
+    DIGIT_NAMES
+        .iter()
+        .position(|&name| name == s)
+        .map(|n| n as u32)
 }
 
 // xs:      🟩🟩🟩        🟧🟧🟧🟧🟧🟧🟧🟧🟧🟧🟧🟧              🟥🟥🟥🟥🟥🟥🟥🟥🟥🟥🟥🟥🟥🟥
@@ -202,6 +211,8 @@ pub fn rotate_mut<T: Copy>(data: &mut [Vec<T>]) {
     }
 }
 
+/// Floating point interpolation through `f` sampled at `x`. See `Polynomial`
+/// for an exact alternative when extrapolating far from the samples.
 #[allow(dead_code)]
 pub fn polynomial_interpolate<F>(x: Vec<f64>, f: F) -> Box<dyn Fn(f64) -> f64>
 where
@@ -1,5 +1,9 @@
 use crate::{Pos, manhattan_distance};
-use std::cmp::{max, min};
+use num::Integer;
+use num::rational::Ratio;
+use std::cell::Cell;
+use std::cmp::{Ordering, max, min};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::iter::{empty, once};
 
 #[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
@@ -8,13 +12,50 @@ pub struct LineSegment {
     pub p2: Pos,
 }
 
+/// The result of intersecting two line segments.
+///
+/// Only lattice points are reported as a `Point`. Two segments that cross
+/// somewhere between grid points (e.g. `(0,0)-(1,1)` and `(0,1)-(1,0)`) are a
+/// `Crossing`; use `LineSegment::crossing_point` to get the exact rational
+/// location.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum Intersection {
+    None,
+    Point(Pos),
+    /// The segments cross at a single point that is not a lattice point.
+    Crossing,
+    Overlap(LineSegment),
+}
+
+/// A lattice point shared by two or more segments, along with the indices of
+/// every segment passing through it.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct SegmentIntersection {
+    pub pos: Pos,
+    pub segments: Vec<usize>,
+}
+
 impl LineSegment {
+    pub fn new(p1: Pos, p2: Pos) -> Self {
+        Self { p1, p2 }
+    }
+
     pub fn vertical(&self) -> bool {
         self.p1.0 == self.p2.0 && self.p1.1 != self.p2.1
     }
 
     pub fn horizontal(&self) -> bool {
-        !self.vertical()
+        self.p1.1 == self.p2.1
+    }
+
+    /// True for 45° segments, i.e. those where x and y change by the same amount.
+    pub fn diagonal(&self) -> bool {
+        let (dx, dy) = self.delta();
+        dx != 0 && dx.abs() == dy.abs()
+    }
+
+    pub fn delta(&self) -> Pos {
+        (self.p2.0 - self.p1.0, self.p2.1 - self.p1.1)
     }
 
     #[allow(clippy::len_without_is_empty)]
@@ -22,60 +63,485 @@ impl LineSegment {
         manhattan_distance(&self.p1, &self.p2)
     }
 
-    pub fn intersect(&self, other: &LineSegment) -> Option<Pos> {
-        let mut i = self.intersections(other);
-        let intersection = i.next();
-        assert!(i.next().is_none(), "More than one intersection");
-        intersection
+    /// Does this segment pass exactly through `pos`?
+    pub fn contains(&self, pos: &Pos) -> bool {
+        orientation(&self.p1, &self.p2, pos) == 0
+            && in_line(self.p1.0, self.p2.0, pos.0)
+            && in_line(self.p1.1, self.p2.1, pos.1)
     }
 
-    pub fn intersections(&self, other: &LineSegment) -> Box<dyn Iterator<Item = Pos> + '_> {
-        if self.vertical() ^ other.vertical() {
-            self.intersections_orthogonal(other)
+    /// Rasterises the segment from `p1` to `p2` (both inclusive) using
+    /// Bresenham's algorithm. Axis-aligned and 45° segments yield exactly the
+    /// lattice points they pass through.
+    pub fn points(&self) -> impl Iterator<Item = Pos> + use<> {
+        let (x2, y2) = self.p2;
+        let (dx, dy) = ((x2 - self.p1.0).abs(), -(y2 - self.p1.1).abs());
+        let (sx, sy) = ((x2 - self.p1.0).signum(), (y2 - self.p1.1).signum());
+        let mut err = dx + dy;
+        let mut current = Some(self.p1);
+
+        std::iter::from_fn(move || {
+            let (x, y) = current?;
+            current = if (x, y) == (x2, y2) {
+                None
+            } else {
+                let (mut x, mut y) = (x, y);
+                let e2 = 2 * err;
+                if e2 >= dy {
+                    err += dy;
+                    x += sx;
+                }
+                if e2 <= dx {
+                    err += dx;
+                    y += sy;
+                }
+                Some((x, y))
+            };
+            Some((x, y))
+        })
+    }
+
+    /// Exact test (using orientation / cross products) of whether the two
+    /// segments share at least one point, lattice or otherwise.
+    pub fn intersects(&self, other: &LineSegment) -> bool {
+        let o1 = orientation(&self.p1, &self.p2, &other.p1);
+        let o2 = orientation(&self.p1, &self.p2, &other.p2);
+        let o3 = orientation(&other.p1, &other.p2, &self.p1);
+        let o4 = orientation(&other.p1, &other.p2, &self.p2);
+
+        (o1 * o2 < 0 && o3 * o4 < 0)
+            || (o1 == 0 && self.contains(&other.p1))
+            || (o2 == 0 && self.contains(&other.p2))
+            || (o3 == 0 && other.contains(&self.p1))
+            || (o4 == 0 && other.contains(&self.p2))
+    }
+
+    /// Where the two segments meet, using exact integer arithmetic.
+    ///
+    /// A single shared point is a `Point` only if it lies on the lattice,
+    /// otherwise it is a `Crossing`. `None` is returned exactly when
+    /// `intersects` is false.
+    pub fn intersect(&self, other: &LineSegment) -> Intersection {
+        let (d1, d2) = (self.delta(), other.delta());
+        let w = (other.p1.0 - self.p1.0, other.p1.1 - self.p1.1);
+        let denom = cross(&d1, &d2);
+
+        if d1 == (0, 0) {
+            return point_if(other.contains(&self.p1), self.p1);
+        }
+        if d2 == (0, 0) {
+            return point_if(self.contains(&other.p1), other.p1);
+        }
+
+        if denom == 0 {
+            return if cross(&d1, &w) == 0 {
+                self.collinear_overlap(other)
+            } else {
+                Intersection::None
+            };
+        }
+
+        let (t, u) = (cross(&w, &d2), cross(&w, &d1));
+        let (t, u, denom) = if denom < 0 {
+            (-t, -u, -denom)
         } else {
-            self.intersections_parallel(other)
+            (t, u, denom)
+        };
+        if !(0..=denom).contains(&t) || !(0..=denom).contains(&u) {
+            return Intersection::None;
         }
-    }
 
-    fn intersections_orthogonal(&self, other: &LineSegment) -> Box<dyn Iterator<Item = Pos> + '_> {
-        let (h, v) = if self.vertical() {
-            (other, self)
+        let (x, y) = (d1.0 as i128 * t, d1.1 as i128 * t);
+        if x % denom == 0 && y % denom == 0 {
+            // The point lies between the endpoints of `self`, so it fits whenever they do.
+            let coord = |p: isize, offset: i128| {
+                isize::try_from(p as i128 + offset).expect("point between isize endpoints")
+            };
+            Intersection::Point((coord(self.p1.0, x / denom), coord(self.p1.1, y / denom)))
         } else {
-            (self, other)
+            Intersection::Crossing
+        }
+    }
+
+    /// Exact location where two non-parallel segments cross, whether or not it
+    /// lies on a lattice point. Kept in `i128`, as the numerator and
+    /// denominator can outgrow `isize` even when the coordinates do not.
+    pub fn crossing_point(&self, other: &LineSegment) -> Option<(Ratio<i128>, Ratio<i128>)> {
+        let (d1, d2) = (self.delta(), other.delta());
+        let w = (other.p1.0 - self.p1.0, other.p1.1 - self.p1.1);
+        let denom = cross(&d1, &d2);
+
+        if denom == 0 || !self.intersects(other) {
+            return None;
+        }
+
+        let t = cross(&w, &d2);
+        let coord = |p: isize, d: isize| Ratio::new(p as i128 * denom + d as i128 * t, denom);
+        Some((coord(self.p1.0, d1.0), coord(self.p1.1, d1.1)))
+    }
+
+    /// All lattice points shared by the two segments.
+    pub fn intersections(&self, other: &LineSegment) -> Box<dyn Iterator<Item = Pos> + '_> {
+        match self.intersect(other) {
+            Intersection::None | Intersection::Crossing => Box::new(empty()),
+            Intersection::Point(p) => Box::new(once(p)),
+            Intersection::Overlap(segment) => Box::new(segment.lattice_points()),
+        }
+    }
+
+    /// Finds every lattice point where two or more of `segments` meet.
+    ///
+    /// A Bentley–Ottmann sweep: a vertical line moves from left to right over
+    /// the event points (endpoints, and crossings as they are found), keeping
+    /// the segments it cuts ordered by y. Two segments can only cross after
+    /// they have become neighbours in that order, so only neighbours are
+    /// tested. Events with the same x are taken from bottom to top, which
+    /// handles vertical segments and any number of segments through a point.
+    /// Collinear overlaps are found when a segment enters next to others on
+    /// the same line.
+    ///
+    /// With `k` crossings (on lattice points or not) this takes
+    /// O((n + k) log n) steps, plus the lattice points of collinear overlaps.
+    /// The results are sorted by position.
+    // The keys of the active set read the sweep point, see `StatusKey`.
+    #[allow(clippy::mutable_key_type)]
+    pub fn all_intersections(segments: &[LineSegment]) -> Vec<SegmentIntersection> {
+        let sweep = Sweep::new(segments);
+        let key = |entry| StatusKey {
+            sweep: &sweep,
+            entry,
         };
-        if in_line(h.p1.0, h.p2.0, v.p1.0) && in_line(v.p1.1, v.p2.1, h.p1.1) {
-            Box::new(once((v.p1.0, h.p1.1)))
+
+        // Each event point, with the segments starting there.
+        let mut queue: BTreeMap<SweepPoint, Vec<usize>> = BTreeMap::new();
+        for (i, &(start, end)) in sweep.ends.iter().enumerate() {
+            queue.entry(sweep_point(start)).or_default().push(i);
+            queue.entry(sweep_point(end)).or_default();
+        }
+
+        let mut status: BTreeSet<StatusKey> = BTreeSet::new();
+        let mut found: BTreeMap<Pos, BTreeSet<usize>> = BTreeMap::new();
+
+        while let Some((p, starting)) = queue.pop_first() {
+            sweep.point.set(p);
+            sweep.after.set(false);
+
+            // The active segments through p, which are next to each other.
+            let through: Vec<usize> = status
+                .range(key(Entry::Below)..key(Entry::Above))
+                .map(StatusKey::index)
+                .collect();
+            if starting.len() + through.len() > 1
+                && let Some(pos) = lattice_point(&p)
+            {
+                found
+                    .entry(pos)
+                    .or_default()
+                    .extend(starting.iter().chain(&through));
+            }
+
+            for &i in &through {
+                status.remove(&key(Entry::Segment(i)));
+            }
+
+            // Put back what continues past p, now ordered as just after p.
+            sweep.after.set(true);
+            let continues = |&i: &usize| sweep_point(sweep.ends[i].1) != p;
+            for &i in starting.iter().chain(&through).filter(|i| continues(i)) {
+                status.insert(key(Entry::Segment(i)));
+            }
+
+            // The segments through p after it, from bottom to top. Those on
+            // the same line are next to each other, so a new one overlapping
+            // others is in a run with them.
+            let group: Vec<usize> = status
+                .range(key(Entry::Below)..key(Entry::Above))
+                .map(StatusKey::index)
+                .collect();
+            for run in group.chunk_by(|&a, &b| sweep.collinear(a, b)) {
+                for &i in run.iter().filter(|i| starting.binary_search(i).is_ok()) {
+                    for &j in run.iter().filter(|&&j| j != i) {
+                        for pos in segments[i].intersections(&segments[j]) {
+                            found.entry(pos).or_default().extend([i, j]);
+                        }
+                    }
+                }
+            }
+
+            // Only the segments around p have new neighbours.
+            let below = status.range(..key(Entry::Below)).next_back();
+            let above = status.range(key(Entry::Above)..).next();
+            let pairs = match (group.first(), group.last()) {
+                (Some(&lowest), Some(&highest)) => [
+                    (below.map(StatusKey::index), Some(lowest)),
+                    (Some(highest), above.map(StatusKey::index)),
+                ],
+                _ => [
+                    (below.map(StatusKey::index), above.map(StatusKey::index)),
+                    (None, None),
+                ],
+            };
+            for (a, b) in pairs {
+                if let (Some(a), Some(b)) = (a, b)
+                    && let Some(q) = sweep.crossing_after(a, b)
+                {
+                    queue.entry(q).or_default();
+                }
+            }
+        }
+
+        found
+            .into_iter()
+            .map(|(pos, segments)| SegmentIntersection {
+                pos,
+                segments: segments.into_iter().collect(),
+            })
+            .collect()
+    }
+
+    /// Counts the lattice points covered by at least `k` of `segments`.
+    pub fn count_covered(segments: &[LineSegment], k: usize) -> usize {
+        if k <= 1 {
+            segments
+                .iter()
+                .flat_map(|s| s.lattice_points())
+                .collect::<HashSet<_>>()
+                .len()
         } else {
-            Box::new(empty())
+            Self::all_intersections(segments)
+                .into_iter()
+                .filter(|i| i.segments.len() >= k)
+                .count()
         }
     }
 
-    #[allow(clippy::reversed_empty_ranges)]
-    fn intersections_parallel(&self, other: &LineSegment) -> Box<dyn Iterator<Item = Pos> + '_> {
-        if self.vertical() && self.p1.0 == other.p1.0 {
-            Box::new(
-                overlap(self.p1.1, self.p2.1, other.p1.1, other.p2.1).map(move |y| (self.p1.0, y)),
-            )
-        } else if self.horizontal() && self.p1.1 == other.p1.1 {
-            Box::new(
-                overlap(self.p1.0, self.p2.0, other.p1.0, other.p2.0).map(move |x| (x, self.p1.1)),
-            )
+    fn collinear_overlap(&self, other: &LineSegment) -> Intersection {
+        // Collinear points are ordered consistently along the line when
+        // compared lexicographically, so the overlap is just a range clamp.
+        let lo = max(min(self.p1, self.p2), min(other.p1, other.p2));
+        let hi = min(max(self.p1, self.p2), max(other.p1, other.p2));
+        match lo.cmp(&hi) {
+            Ordering::Less => Intersection::Overlap(LineSegment::new(lo, hi)),
+            Ordering::Equal => Intersection::Point(lo),
+            Ordering::Greater => Intersection::None,
+        }
+    }
+
+    fn lattice_points(&self) -> impl Iterator<Item = Pos> + use<> {
+        let (dx, dy) = self.delta();
+        let steps = dx.gcd(&dy);
+        let (sx, sy) = if steps == 0 {
+            (0, 0)
         } else {
-            Box::new(empty())
+            (dx / steps, dy / steps)
+        };
+        let p1 = self.p1;
+        (0..=steps).map(move |i| (p1.0 + i * sx, p1.1 + i * sy))
+    }
+}
+
+/// An event point of the sweep in `LineSegment::all_intersections`, ordered
+/// by x and then y.
+type SweepPoint = (Ratio<i128>, Ratio<i128>);
+
+fn sweep_point(pos: Pos) -> SweepPoint {
+    (
+        Ratio::from_integer(pos.0 as i128),
+        Ratio::from_integer(pos.1 as i128),
+    )
+}
+
+fn lattice_point(point: &SweepPoint) -> Option<Pos> {
+    let coord = |c: &Ratio<i128>| {
+        c.is_integer()
+            .then(|| isize::try_from(*c.numer()).expect("point on an isize segment"))
+    };
+    Some((coord(&point.0)?, coord(&point.1)?))
+}
+
+/// The segments of `LineSegment::all_intersections` and the position of its
+/// sweep, which orders the active segments.
+struct Sweep<'a> {
+    segments: &'a [LineSegment],
+    /// The endpoints of each segment, in event order.
+    ends: Vec<(Pos, Pos)>,
+    point: Cell<SweepPoint>,
+    /// Whether segments through `point` are ordered as just after it, rather
+    /// than as just before it.
+    after: Cell<bool>,
+}
+
+/// An entry of the active set, or a probe just below or above the sweep point
+/// to find the segments through it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Entry {
+    Below,
+    Segment(usize),
+    Above,
+}
+
+impl<'a> Sweep<'a> {
+    fn new(segments: &'a [LineSegment]) -> Self {
+        Self {
+            segments,
+            ends: segments
+                .iter()
+                .map(|s| (min(s.p1, s.p2), max(s.p1, s.p2)))
+                .collect(),
+            point: Cell::new(sweep_point((0, 0))),
+            after: Cell::new(false),
+        }
+    }
+
+    /// Points to the right, or up for a vertical segment.
+    fn direction(&self, i: usize) -> Pos {
+        let (start, end) = self.ends[i];
+        (end.0 - start.0, end.1 - start.1)
+    }
+
+    /// Where segment `i` is cut by the sweep, as a fraction with a positive
+    /// denominator. A vertical segment is only active while the sweep point is
+    /// on it.
+    fn y_at(&self, i: usize) -> (i128, i128) {
+        let (px, py) = self.point.get();
+        let (start, _) = self.ends[i];
+        let (dx, dy) = self.direction(i);
+        if dx == 0 {
+            return (*py.numer(), *py.denom());
+        }
+
+        let (n, d) = (*px.numer(), *px.denom());
+        let (dx, dy) = (dx as i128, dy as i128);
+        (
+            start.1 as i128 * d * dx + (n - start.0 as i128 * d) * dy,
+            d * dx,
+        )
+    }
+
+    fn compare(&self, a: Entry, b: Entry) -> Ordering {
+        match (a, b) {
+            (Entry::Segment(i), Entry::Segment(j)) => self.compare_segments(i, j),
+            (Entry::Segment(i), probe) => {
+                let py = self.point.get().1;
+                cmp_fraction(self.y_at(i), (*py.numer(), *py.denom())).then(
+                    if probe == Entry::Below {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Less
+                    },
+                )
+            }
+            (_, Entry::Segment(_)) => self.compare(b, a).reverse(),
+            _ => (a == Entry::Above).cmp(&(b == Entry::Above)),
+        }
+    }
+
+    fn compare_segments(&self, i: usize, j: usize) -> Ordering {
+        if i == j {
+            return Ordering::Equal;
+        }
+
+        let y = self.y_at(i);
+        cmp_fraction(y, self.y_at(j)).then_with(|| {
+            // Both pass through the same point of the sweep. Past that point,
+            // the flatter one is below; before it, the steeper one is.
+            let py = self.point.get().1;
+            let after = match cmp_fraction(y, (*py.numer(), *py.denom())) {
+                Ordering::Less => true,
+                Ordering::Equal => self.after.get(),
+                Ordering::Greater => false,
+            };
+            let by_slope = 0.cmp(&cross(&self.direction(i), &self.direction(j)));
+            let by_slope = if after { by_slope } else { by_slope.reverse() };
+            by_slope.then(i.cmp(&j))
+        })
+    }
+
+    fn collinear(&self, i: usize, j: usize) -> bool {
+        let (a, b) = (self.ends[i].0, self.ends[j].0);
+        let d = self.direction(i);
+        cross(&d, &self.direction(j)) == 0 && cross(&d, &(b.0 - a.0, b.1 - a.1)) == 0
+    }
+
+    /// Where segments `i` and `j` cross, if that is still ahead of the sweep.
+    fn crossing_after(&self, i: usize, j: usize) -> Option<SweepPoint> {
+        if cross(&self.direction(i), &self.direction(j)) == 0 {
+            return None;
+        }
+        let q = self.segments[i].crossing_point(&self.segments[j])?;
+        (q > self.point.get()).then_some(q)
+    }
+}
+
+/// An `Entry` of the active set, ordered by where the sweep cuts it.
+///
+/// The order depends on the sweep point, but the relative order of the active
+/// segments only changes at event points, where the sweep takes out and puts
+/// back the segments through the point.
+struct StatusKey<'s, 'a> {
+    sweep: &'s Sweep<'a>,
+    entry: Entry,
+}
+
+impl StatusKey<'_, '_> {
+    fn index(&self) -> usize {
+        match self.entry {
+            Entry::Segment(i) => i,
+            _ => unreachable!("probes are not stored"),
         }
     }
 }
 
+impl Ord for StatusKey<'_, '_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sweep.compare(self.entry, other.entry)
+    }
+}
+
+impl PartialOrd for StatusKey<'_, '_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for StatusKey<'_, '_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for StatusKey<'_, '_> {}
+
+/// Compares `a.0 / a.1` with `b.0 / b.1`, for positive denominators.
+fn cmp_fraction(a: (i128, i128), b: (i128, i128)) -> Ordering {
+    match (a.0.checked_mul(b.1), b.0.checked_mul(a.1)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        _ => Ratio::new(a.0, a.1).cmp(&Ratio::new(b.0, b.1)),
+    }
+}
+
 #[inline]
-fn in_line(x1: isize, x2: isize, x: isize) -> bool {
-    (x1 <= x && x <= x2) || (x2 <= x && x <= x1)
+fn point_if(condition: bool, pos: Pos) -> Intersection {
+    if condition {
+        Intersection::Point(pos)
+    } else {
+        Intersection::None
+    }
+}
+
+#[inline]
+fn cross(a: &Pos, b: &Pos) -> i128 {
+    a.0 as i128 * b.1 as i128 - a.1 as i128 * b.0 as i128
+}
+
+#[inline]
+fn orientation(a: &Pos, b: &Pos, c: &Pos) -> i128 {
+    cross(&(b.0 - a.0, b.1 - a.1), &(c.0 - a.0, c.1 - a.1)).signum()
 }
 
 #[inline]
-fn overlap(i1: isize, i2: isize, j1: isize, j2: isize) -> std::ops::RangeInclusive<isize> {
-    let k1 = max(min(i1, i2), min(j1, j2));
-    let k2 = min(max(i1, i2), max(j1, j2));
-    k1..=k2
+fn in_line(x1: isize, x2: isize, x: isize) -> bool {
+    (x1 <= x && x <= x2) || (x2 <= x && x <= x1)
 }
 
 #[cfg(test)]
@@ -94,70 +560,70 @@ mod tests {
     fn test_intersect1() {
         let a = line(1, 5, 1, 9);
         let b = line(0, 7, 12, 7);
-        assert_eq!(a.intersect(&b), Some((1, 7)));
+        assert_eq!(a.intersect(&b), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect2() {
         let a = line(1, 5, 1, 9);
         let b = line(12, 7, 0, 7);
-        assert_eq!(a.intersect(&b), Some((1, 7)));
+        assert_eq!(a.intersect(&b), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect3() {
         let a = line(1, 9, 1, 5);
         let b = line(0, 7, 12, 7);
-        assert_eq!(a.intersect(&b), Some((1, 7)));
+        assert_eq!(a.intersect(&b), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect4() {
         let a = line(1, 9, 1, 5);
         let b = line(12, 7, 0, 7);
-        assert_eq!(a.intersect(&b), Some((1, 7)));
+        assert_eq!(a.intersect(&b), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect5() {
         let a = line(1, 5, 1, 9);
         let b = line(0, 7, 12, 7);
-        assert_eq!(b.intersect(&a), Some((1, 7)));
+        assert_eq!(b.intersect(&a), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect6() {
         let a = line(1, 5, 1, 9);
         let b = line(12, 7, 0, 7);
-        assert_eq!(b.intersect(&a), Some((1, 7)));
+        assert_eq!(b.intersect(&a), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect7() {
         let a = line(1, 9, 1, 5);
         let b = line(0, 7, 12, 7);
-        assert_eq!(b.intersect(&a), Some((1, 7)));
+        assert_eq!(b.intersect(&a), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect8() {
         let a = line(1, 9, 1, 5);
         let b = line(12, 7, 0, 7);
-        assert_eq!(b.intersect(&a), Some((1, 7)));
+        assert_eq!(b.intersect(&a), Intersection::Point((1, 7)));
     }
 
     #[test]
     fn test_intersect_none1() {
         let a = line(1, 9, 1, 5);
         let b = line(2, 7, 12, 7);
-        assert_eq!(a.intersect(&b), None);
+        assert_eq!(a.intersect(&b), Intersection::None);
     }
 
     #[test]
     fn test_intersect_none2() {
         let a = line(3, 5, 3, 2);
         let b = line(0, 7, 6, 7);
-        assert_eq!(a.intersect(&b), None);
+        assert_eq!(a.intersect(&b), Intersection::None);
     }
 
     #[test]
@@ -179,4 +645,215 @@ mod tests {
             vec![(5, 7), (6, 7)]
         );
     }
+
+    #[test]
+    fn test_intersect_overlap() {
+        let a = line(3, 5, 3, 2);
+        let b = line(3, 7, 3, 3);
+        assert_eq!(a.intersect(&b), Intersection::Overlap(line(3, 3, 3, 5)));
+    }
+
+    #[test]
+    fn test_intersect_diagonal() {
+        let a = line(0, 0, 8, 8);
+        let b = line(0, 8, 8, 0);
+        assert!(a.diagonal() && b.diagonal());
+        assert_eq!(a.intersect(&b), Intersection::Point((4, 4)));
+    }
+
+    #[test]
+    fn test_intersect_diagonal_overlap() {
+        let a = line(0, 0, 4, 4);
+        let b = line(6, 6, 2, 2);
+        assert_eq!(
+            a.intersections(&b).collect::<Vec<_>>(),
+            vec![(2, 2), (3, 3), (4, 4)]
+        );
+    }
+
+    #[test]
+    fn test_intersect_between_lattice_points() {
+        let a = line(0, 0, 1, 1);
+        let b = line(0, 1, 1, 0);
+        assert!(a.intersects(&b));
+        assert_eq!(a.intersect(&b), Intersection::Crossing);
+        assert_eq!(b.intersect(&a), Intersection::Crossing);
+        assert_eq!(a.intersections(&b).count(), 0);
+        assert_eq!(
+            a.crossing_point(&b),
+            Some((Ratio::new(1, 2), Ratio::new(1, 2)))
+        );
+
+        let c = line(0, 0, 6, 3);
+        let d = line(0, 3, 3, 0);
+        assert_eq!(c.intersect(&d), Intersection::Point((2, 1)));
+        assert_eq!(c.intersect(&line(0, 2, 3, 0)), Intersection::Crossing);
+        assert_eq!(c.intersect(&line(0, 4, 1, 4)), Intersection::None);
+    }
+
+    #[test]
+    fn test_crossing_point_large_coordinates() {
+        // The denominator is 2 * 10^24, far beyond isize.
+        let big = 1_000_000_000_000;
+        let a = line(0, 0, big, big + 1);
+        let b = line(0, big, big + 1, 0);
+        let (x, y) = a.crossing_point(&b).unwrap();
+        let wide = big as i128;
+        assert_eq!(*x.denom(), 2 * wide * wide + 2 * wide + 1);
+        assert_eq!(x, y * Ratio::new(wide, wide + 1));
+        assert_eq!(
+            line(0, 0, big, big).intersect(&line(0, big, big, 0)),
+            Intersection::Point((big / 2, big / 2))
+        );
+    }
+
+    #[test]
+    fn test_intersect_arbitrary_slope() {
+        let a = line(0, 0, 6, 2);
+        let b = line(3, 0, 3, 5);
+        assert_eq!(a.intersect(&b), Intersection::Point((3, 1)));
+        assert!(!a.intersects(&line(0, 1, 2, 3)));
+    }
+
+    #[test]
+    fn test_all_intersections() {
+        let segments = [
+            line(0, 9, 5, 9),
+            line(8, 0, 0, 8),
+            line(9, 4, 3, 4),
+            line(2, 2, 2, 1),
+            line(7, 0, 7, 4),
+            line(6, 4, 2, 0),
+            line(0, 9, 2, 9),
+            line(3, 4, 1, 4),
+            line(0, 0, 8, 8),
+            line(5, 5, 8, 2),
+        ];
+        assert_eq!(LineSegment::count_covered(&segments, 2), 12);
+        assert_eq!(LineSegment::count_covered(&segments, 3), 2);
+
+        let intersections = LineSegment::all_intersections(&segments);
+        assert_eq!(
+            intersections.iter().find(|i| i.pos == (4, 4)),
+            Some(&SegmentIntersection {
+                pos: (4, 4),
+                segments: vec![1, 2, 8],
+            })
+        );
+    }
+
+    fn assert_matches_pairwise(segments: &[LineSegment]) {
+        let mut pairwise: BTreeMap<Pos, BTreeSet<usize>> = BTreeMap::new();
+        for i in 0..segments.len() {
+            for j in (i + 1)..segments.len() {
+                for pos in segments[i].intersections(&segments[j]) {
+                    pairwise.entry(pos).or_default().extend([i, j]);
+                }
+            }
+        }
+        let swept: BTreeMap<Pos, BTreeSet<usize>> = LineSegment::all_intersections(segments)
+            .into_iter()
+            .map(|i| (i.pos, i.segments.into_iter().collect()))
+            .collect();
+        assert_eq!(swept, pairwise, "{segments:?}");
+    }
+
+    #[test]
+    fn test_all_intersections_matches_pairwise() {
+        assert_matches_pairwise(&[
+            line(0, 0, 10, 3),
+            line(0, 3, 10, 0),
+            line(5, -2, 5, 8),
+            line(-1, 1, 11, 1),
+            line(3, 3, 9, -3),
+        ]);
+
+        // Crowded small grids: vertical, collinear, touching and zero length
+        // segments, and many segments through the same points.
+        let mut seed: u64 = 1;
+        let mut next = |n: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((seed >> 33) % n) as isize
+        };
+        for round in 0..500 {
+            let size = 3 + round as u64 % 6;
+            let segments: Vec<LineSegment> = (0..2 + round % 12)
+                .map(|_| {
+                    let (x, y) = (next(size), next(size));
+                    match next(4) {
+                        0 => line(x, y, x, next(size)),
+                        1 => line(x, y, next(size), y),
+                        2 => {
+                            let d = next(size) - x;
+                            line(x, y, x + d, y + d)
+                        }
+                        _ => line(x, y, next(size), next(size)),
+                    }
+                })
+                .collect();
+            assert_matches_pairwise(&segments);
+        }
+    }
+
+    #[test]
+    fn test_all_intersections_disjoint_long_segments() {
+        // Stacked segments sharing their whole x-range, but not their y, and
+        // two vertical segments crossing all of them: one between lattice
+        // points, one through their left ends.
+        let n = 20_000;
+        let mut segments: Vec<LineSegment> = (0..n)
+            .map(|i| line(0, 2 * i, 1_000_000, 2 * i + 1))
+            .collect();
+        segments.push(line(500, -1, 500, 2 * n));
+        segments.push(line(0, -1, 0, 2 * n));
+
+        let intersections = LineSegment::all_intersections(&segments);
+        assert_eq!(intersections.len(), n as usize);
+        assert!(
+            intersections
+                .iter()
+                .all(|i| i.pos.0 == 0 && i.segments == vec![i.pos.1 as usize / 2, n as usize + 1])
+        );
+    }
+
+    #[test]
+    fn test_all_intersections_overlapping_long_segments() {
+        // Long parallel diagonals whose bounding boxes all overlap, crossed by
+        // a horizontal and a steep segment. Comparing every pair would take
+        // 2 * 10^8 steps.
+        let n = 20_000;
+        let mut segments: Vec<LineSegment> = (0..n)
+            .map(|i| line(2 * i, 0, 2 * i + 1_000_000, 1_000_000))
+            .collect();
+        segments.push(line(0, 500_000, 2_000_000, 500_000));
+        segments.push(line(0, 0, 2 * n + 1, 2_000_000));
+
+        let intersections = LineSegment::all_intersections(&segments);
+        assert_eq!(intersections.len(), n as usize + 1);
+        assert_eq!(intersections[0].pos, (0, 0));
+        assert_eq!(intersections[0].segments, vec![0, n as usize + 1]);
+        assert!(
+            intersections[1..]
+                .iter()
+                .all(|i| i.pos.1 == 500_000 && i.segments.contains(&(n as usize)))
+        );
+    }
+
+    #[test]
+    fn test_points() {
+        assert_eq!(
+            line(0, 0, 3, 3).points().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 1), (2, 2), (3, 3)]
+        );
+        assert_eq!(
+            line(2, 5, 2, 3).points().collect::<Vec<_>>(),
+            vec![(2, 5), (2, 4), (2, 3)]
+        );
+        assert_eq!(
+            line(0, 0, 5, 2).points().collect::<Vec<_>>(),
+            vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]
+        );
+    }
 }
@@ -1,7 +1,11 @@
 extern crate advent_of_code;
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{all, download, read, scaffold, solve, time, verify};
+use advent_of_code::template::runner::Registry;
 use args::{AppArguments, parse};
 
+#[cfg(feature = "registry")]
+mod registry;
+
 #[cfg(feature = "today")]
 use advent_of_code::template::Day;
 #[cfg(feature = "today")]
@@ -9,8 +13,33 @@ use std::process;
 
 mod args {
     use crate::advent_of_code::template::Day;
+    use crate::advent_of_code::template::commands::time::Comparison;
+    use crate::advent_of_code::template::limits::Limits;
     use std::process;
 
+    const HELP: &str = "\
+Usage: cargo <command> [options]
+
+Commands:
+  scaffold <day> [--download]        Create the files of a day.
+  download <day>                     Download the puzzle and input of a day.
+  read <day>                         Print the puzzle description of a day.
+  solve <day> [--release] [--dhat] [--submit <part>] [--quiet] [--examples] [--param <value>]...
+                                     Run the solution of a day.
+  all [--release] [--jobs <n>]       Run all solutions.
+  time [<day>] [--all] [--store] [--budget <ms>] [--jobs <n>] [--compare [<ref>]] [--threshold <percent>]
+                                     Bench solutions and update the readme.
+  verify [<day>] [--all]             Check the answers against the stored ones.
+  today                              Scaffold and download today's puzzle.
+
+Limits (solve, all, time):
+  --timeout <seconds>                Stop a day that runs longer than this.
+  --max-memory <MiB>                 Stop a day that holds more heap than this.
+
+`all` and `time` run the days linked into this binary, which cannot be stopped from the outside.
+With --timeout, --max-memory or --jobs, each day runs in its own process instead, which is slower.
+";
+
     pub enum AppArguments {
         Download {
             day: Day,
@@ -28,36 +57,93 @@ mod args {
             dhat: bool,
             submit: Option<u8>,
             quiet: bool,
+            examples: bool,
+            params: Vec<String>,
+            limits: Limits,
         },
         All {
             release: bool,
+            jobs: Option<usize>,
+            limits: Limits,
         },
         Time {
             all: bool,
             day: Option<Day>,
             store: bool,
+            budget: Option<u64>,
+            jobs: Option<usize>,
+            limits: Limits,
+            comparison: Comparison,
+        },
+        Verify {
+            all: bool,
+            day: Option<Day>,
         },
         #[cfg(feature = "today")]
         Today,
     }
 
+    /// Reads `--timeout <seconds>` and `--max-memory <MiB>`.
+    fn parse_limits(args: &mut pico_args::Arguments) -> Result<Limits, pico_args::Error> {
+        Ok(Limits::new(
+            args.opt_value_from_str("--timeout")?,
+            args.opt_value_from_str("--max-memory")?,
+        ))
+    }
+
+    /// Reads `--compare [ref]`. Without a ref, the run is compared with the previous one.
+    /// Parsed after other options, so that a trailing flag is not mistaken for a ref.
+    fn parse_compare(
+        args: &mut pico_args::Arguments,
+    ) -> Result<Option<Option<String>>, pico_args::Error> {
+        match args.opt_value_from_str("--compare") {
+            Ok(reference) => Ok(reference.map(Some)),
+            Err(pico_args::Error::OptionWithoutAValue(_)) => {
+                Ok(args.contains("--compare").then_some(None))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
         let mut args = pico_args::Arguments::from_env();
 
+        if args.contains(["-h", "--help"]) {
+            print!("{HELP}");
+            process::exit(0);
+        }
+
         let app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
+                jobs: args.opt_value_from_str("--jobs")?,
+                limits: parse_limits(&mut args)?,
             },
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let budget = args.opt_value_from_str("--budget")?;
+                let jobs = args.opt_value_from_str("--jobs")?;
+                let limits = parse_limits(&mut args)?;
+                let comparison = Comparison {
+                    threshold: args.opt_value_from_str("--threshold")?.unwrap_or(10.0),
+                    compare: parse_compare(&mut args)?,
+                };
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    budget,
+                    jobs,
+                    limits,
+                    comparison,
                 }
             }
+            Some("verify") => AppArguments::Verify {
+                all: args.contains("--all"),
+                day: args.opt_free_from_str()?,
+            },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
             },
@@ -74,6 +160,9 @@ mod args {
                 submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
                 quiet: args.contains("--quiet"),
+                examples: args.contains("--examples"),
+                params: args.values_from_str("--param")?,
+                limits: parse_limits(&mut args)?,
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -82,7 +171,7 @@ mod args {
                 process::exit(1);
             }
             None => {
-                eprintln!("No command specified.");
+                eprintln!("No command specified.\n\n{HELP}");
                 process::exit(1);
             }
         };
@@ -96,6 +185,17 @@ mod args {
     }
 }
 
+/// Days linked into this binary, so that `all` and `time` run them in-process.
+#[cfg(feature = "registry")]
+fn registry() -> Option<Registry> {
+    Some(registry::registry())
+}
+
+#[cfg(not(feature = "registry"))]
+fn registry() -> Option<Registry> {
+    None
+}
+
 fn main() {
     match parse() {
         Err(err) => {
@@ -103,8 +203,30 @@ fn main() {
             std::process::exit(1);
         }
         Ok(args) => match args {
-            AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::All {
+                release,
+                jobs,
+                limits,
+            } => all::handle(release, jobs, limits, registry().as_ref()),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                budget,
+                jobs,
+                limits,
+                comparison,
+            } => time::handle(
+                day,
+                all,
+                store,
+                budget,
+                jobs,
+                limits,
+                comparison,
+                registry().as_ref(),
+            ),
+            AppArguments::Verify { day, all } => verify::handle(day, all),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold { day, download } => {
@@ -119,7 +241,10 @@ fn main() {
                 dhat,
                 submit,
                 quiet,
-            } => solve::handle(day, release, dhat, submit, quiet),
+                examples,
+                params,
+                limits,
+            } => solve::handle(day, release, dhat, submit, quiet, examples, &params, limits),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
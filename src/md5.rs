@@ -1,6 +1,192 @@
+use crate::{Digest, Md5, NibbleIter, RepeatedRuns, hex_digit};
 use rayon::prelude::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 const CHUNK_SIZE: usize = 100_000;
+const SUFFIX_CAPACITY: usize = 32;
+
+/// Appends the suffix for candidate `n` to the (empty) `buf`.
+pub type SuffixWriter = fn(usize, &mut Vec<u8>);
+
+/// Shared flag for stopping a running search from elsewhere (e.g. another
+/// thread, or a `test` closure that has seen enough).
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Searches `hash(prefix + suffix(n))` for `n = start, start + 1, ...` in
+/// parallel chunks, yielding matches in order of `n`. The hash is any
+/// `Digest`, with `Md5Search` for the usual case.
+///
+/// ```
+/// let found: Vec<_> = Md5Search::new("abc")
+///     .start(3_231_000)
+///     .chunk_size(10_000)
+///     .search(|d| leading_zero_nibbles(d, 5))
+///     .take(1)
+///     .collect();
+/// assert_eq!(found[0].0, 3_231_929);
+/// ```
+#[derive(Clone)]
+pub struct DigestSearch<D: Digest, W = SuffixWriter> {
+    hasher: D,
+    start: usize,
+    end: usize,
+    chunk_size: usize,
+    suffix: W,
+    cancel: CancelToken,
+}
+
+pub type Md5Search = DigestSearch<Md5>;
+
+impl<D: Digest> DigestSearch<D> {
+    pub fn new(prefix: &str) -> Self {
+        let mut hasher = D::default();
+        hasher.update(prefix.trim());
+        Self {
+            hasher,
+            start: 0,
+            end: usize::MAX,
+            chunk_size: CHUNK_SIZE,
+            suffix: write_decimal,
+            cancel: CancelToken::new(),
+        }
+    }
+}
+
+impl<D, W> DigestSearch<D, W>
+where
+    D: Digest,
+    W: Fn(usize, &mut Vec<u8>) + Send + Sync,
+{
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Stop searching (exclusively) at `end` rather than running forever.
+    pub fn end(mut self, end: usize) -> Self {
+        self.end = end;
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "Chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn cancel_token(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Replaces the default decimal suffix. The writer appends to a buffer
+    /// that is reused between candidates, so suffixes of any length can be
+    /// formatted without allocating each time.
+    pub fn suffix<V>(self, suffix: V) -> DigestSearch<D, V>
+    where
+        V: Fn(usize, &mut Vec<u8>) + Send + Sync,
+    {
+        DigestSearch {
+            hasher: self.hasher,
+            start: self.start,
+            end: self.end,
+            chunk_size: self.chunk_size,
+            suffix,
+            cancel: self.cancel,
+        }
+    }
+
+    pub fn digest(&self, n: usize) -> D::Output {
+        self.digest_with(n, &mut Vec::with_capacity(SUFFIX_CAPACITY))
+    }
+
+    /// `digest`, writing the suffix into the reusable `buf`.
+    fn digest_with(&self, n: usize, buf: &mut Vec<u8>) -> D::Output {
+        buf.clear();
+        (self.suffix)(n, buf);
+        let mut hasher = self.hasher.clone();
+        hasher.update(&buf[..]);
+        hasher.finalize()
+    }
+
+    /// Yields `(n, digest)` for every candidate whose digest passes `test`.
+    /// Ends when `end` is reached or the cancel token is triggered. Matches
+    /// from the chunk that was running when cancelled are dropped, as the
+    /// candidates before them may not have been tested.
+    pub fn search<T>(self, test: T) -> impl Iterator<Item = (usize, D::Output)>
+    where
+        T: Fn(&D::Output) -> bool + Send + Sync,
+    {
+        let mut chunk_start = self.start;
+
+        std::iter::from_fn(move || {
+            while chunk_start < self.end && !self.cancel.is_cancelled() {
+                let chunk_end = chunk_start.saturating_add(self.chunk_size).min(self.end);
+                let found: Vec<_> = (chunk_start..chunk_end)
+                    .into_par_iter()
+                    .map_init(
+                        || Vec::with_capacity(SUFFIX_CAPACITY),
+                        |buf, n| {
+                            if self.cancel.is_cancelled() {
+                                return None;
+                            }
+                            let digest = self.digest_with(n, buf);
+                            test(&digest).then_some((n, digest))
+                        },
+                    )
+                    .flatten()
+                    .collect();
+
+                if self.cancel.is_cancelled() {
+                    return None;
+                }
+                chunk_start = chunk_end;
+
+                if !found.is_empty() {
+                    return Some(found);
+                }
+            }
+            None
+        })
+        .flatten()
+    }
+}
+
+/// Does the hex form of `digest` start with `n` zeroes? Checks the bytes
+/// directly rather than formatting the digest. False when `n` is longer than
+/// the hex form itself.
+#[inline]
+pub fn leading_zero_nibbles(digest: &[u8], n: usize) -> bool {
+    n <= 2 * digest.len()
+        && digest[..n / 2].iter().all(|&b| b == 0)
+        && (n.is_multiple_of(2) || digest.get(n / 2).is_some_and(|&b| b < 0x10))
+}
+
+/// The first `n >= 1` such that `md5(prefix + n)` starts with `n_nibbles` zeroes in hex.
+pub fn find_first_with_leading_zeros(prefix: &str, n_nibbles: usize) -> Option<usize> {
+    Md5Search::new(prefix)
+        .start(1)
+        .search(move |digest| leading_zero_nibbles(digest, n_nibbles))
+        .map(|(n, _)| n)
+        .next()
+}
 
 pub fn md5_search<T, I, S>(prefix: &str, test: T, item: I) -> impl Iterator<Item = S>
 where
@@ -8,33 +194,35 @@ where
     I: Fn(&[u8; 16]) -> S + Send + Sync + Clone,
     S: Send + Sync + Ord,
 {
-    let mut context = md5::Context::new();
-    context.consume(prefix.trim());
-
-    let mut chunk_start = 1;
-
-    std::iter::from_fn(move || {
-        loop {
-            let chunk_end = chunk_start + CHUNK_SIZE;
-            let mut found: Vec<_> = (chunk_start..chunk_end)
-                .into_par_iter()
-                .filter_map(|n| {
-                    let mut c2 = context.clone();
-                    c2.consume(n.to_string());
-                    let digest = c2.finalize();
-                    test(&digest.0).then(|| (n, item(&digest.0)))
-                })
-                .collect();
+    digest_search::<Md5, _, _, _>(prefix, test, item)
+}
 
-            chunk_start = chunk_end;
+pub fn digest_search<D, T, I, S>(prefix: &str, test: T, item: I) -> impl Iterator<Item = S>
+where
+    D: Digest,
+    T: Fn(&D::Output) -> bool + Send + Sync + Clone,
+    I: Fn(&D::Output) -> S + Send + Sync + Clone,
+    S: Send + Sync + Ord,
+{
+    DigestSearch::<D>::new(prefix)
+        .start(1)
+        .search(test)
+        .map(move |(_, digest)| item(&digest))
+}
 
-            if !found.is_empty() {
-                found.sort();
-                return Some(found.into_iter().map(|(_, s)| s));
-            }
+/// Appends `n` in decimal, without allocating beyond `buf`'s capacity.
+fn write_decimal(mut n: usize, buf: &mut Vec<u8>) {
+    let mut digits = [0; 20];
+    let mut len = 0;
+    loop {
+        digits[len] = b'0' + (n % 10) as u8;
+        len += 1;
+        n /= 10;
+        if n == 0 {
+            break;
         }
-    })
-    .flatten()
+    }
+    buf.extend(digits[..len].iter().rev());
 }
 
 pub fn nested_md5_list(
@@ -42,33 +230,266 @@ pub fn nested_md5_list(
     nesting: usize,
     rng: std::ops::Range<usize>,
 ) -> impl ParallelIterator<Item = (usize, [u8; 16])> {
+    nested_digest_list::<Md5>(prefix, nesting, rng)
+}
+
+pub fn nested_digest_list<D: Digest>(
+    prefix: &str,
+    nesting: usize,
+    rng: std::ops::Range<usize>,
+) -> impl ParallelIterator<Item = (usize, D::Output)> {
     let prefix = prefix.trim();
 
-    rng.into_par_iter().map(move |n| {
-        let mut context = md5::Context::new();
-        context.consume(prefix);
-        context.consume(n.to_string());
-        let mut digest = context.finalize();
-
-        for _ in 0..nesting {
-            context = md5::Context::new();
-            for n in digest.0 {
-                context.consume(&[hexchar_u8(n / 16), hexchar_u8(n % 16)]);
-            }
-            digest = context.finalize();
+    rng.into_par_iter()
+        .map(move |n| (n, stretched_digest::<D>(prefix, nesting, n)))
+}
+
+/// `hash(prefix + n)`, then re-hashed `nesting` more times as lowercase hex.
+fn stretched_digest<D: Digest>(prefix: &str, nesting: usize, n: usize) -> D::Output {
+    let mut hasher = D::default();
+    hasher.update(prefix);
+    hasher.update(n.to_string());
+    let mut digest = hasher.finalize();
+
+    for _ in 0..nesting {
+        hasher = D::default();
+        for &n in digest.as_ref() {
+            hasher.update([hex_digit(n / 16), hex_digit(n % 16)]);
         }
+        digest = hasher.finalize();
+    }
 
-        (n, digest.0)
-    })
+    digest
 }
 
-#[inline]
-fn hexchar_u8(n: u8) -> u8 {
-    if n < 10 {
-        48 + n
-    } else if n < 16 {
-        87 + n
-    } else {
-        panic!("Invalid hex value");
+/* -------------------------------------------------------------------------- */
+
+const STREAM_BATCH: usize = 1024;
+
+/// An ordered, cached stream of (possibly stretched) hashes of `prefix + n`.
+///
+/// Hashes are computed lazily in parallel batches and kept in a ring buffer,
+/// so look-ahead queries like "does any of the next 1000 hashes contain a run
+/// of five" don't recompute anything as `n` advances. Only hashes from the
+/// most recent `capacity` indices are kept; asking for an earlier one panics.
+pub struct HashStream<D: Digest = Md5> {
+    prefix: String,
+    nesting: usize,
+    batch_size: usize,
+    capacity: usize,
+    first: usize,
+    buffer: VecDeque<D::Output>,
+}
+
+impl<D: Digest> HashStream<D> {
+    pub fn new(prefix: &str, nesting: usize) -> Self {
+        Self {
+            prefix: prefix.trim().to_string(),
+            nesting,
+            batch_size: STREAM_BATCH,
+            capacity: 2 * STREAM_BATCH,
+            first: 0,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "Batch size must be positive");
+        self.batch_size = batch_size;
+        self.capacity = self.capacity.max(2 * batch_size);
+        self
+    }
+
+    pub fn get(&mut self, n: usize) -> D::Output {
+        self.fill(n + 1);
+        self.buffer[self.offset(n)]
+    }
+
+    /// The `len` hashes starting at index `n`.
+    pub fn window(&mut self, n: usize, len: usize) -> impl Iterator<Item = &D::Output> {
+        // Make sure the whole window survives the eviction after the last batch.
+        self.capacity = self.capacity.max(len + self.batch_size);
+        self.fill(n + len);
+        let start = self.offset(n);
+        self.buffer.range(start..start + len)
+    }
+
+    /// If hash `n` has a run of at least `run` equal nibbles, and a run of at
+    /// least `confirm` of that same nibble appears within the following
+    /// `lookahead` hashes, returns the nibble. Only the first run in hash `n`
+    /// is considered.
+    pub fn confirmed_run(
+        &mut self,
+        n: usize,
+        run: usize,
+        confirm: usize,
+        lookahead: usize,
+    ) -> Option<u8> {
+        let (value, _, _) = self.get(n).as_ref().nibbles().first_run_at_least(run)?;
+        self.window(n + 1, lookahead)
+            .any(|digest| {
+                digest
+                    .as_ref()
+                    .nibbles()
+                    .repeated(confirm)
+                    .any(|v| v == value)
+            })
+            .then_some(value)
+    }
+
+    fn offset(&self, n: usize) -> usize {
+        assert!(n >= self.first, "Hash {n} has already been evicted");
+        n - self.first
+    }
+
+    /// Makes sure every index below `end` has been computed.
+    fn fill(&mut self, end: usize) {
+        while self.first + self.buffer.len() < end {
+            let start = self.first + self.buffer.len();
+            let batch: Vec<_> = (start..start + self.batch_size)
+                .into_par_iter()
+                .map(|n| stretched_digest::<D>(&self.prefix, self.nesting, n))
+                .collect();
+            self.buffer.extend(batch);
+
+            while self.buffer.len() > self.capacity {
+                self.buffer.pop_front();
+                self.first += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KnotHash, Sha256};
+
+    #[test]
+    fn test_write_decimal() {
+        let mut buf = vec![];
+        write_decimal(609043, &mut buf);
+        assert_eq!(buf, b"609043");
+        buf.clear();
+        write_decimal(0, &mut buf);
+        assert_eq!(buf, b"0");
+    }
+
+    #[test]
+    fn test_leading_zero_nibbles() {
+        let digest = Md5Search::new("abcdef").digest(609043);
+        assert!(digest.to_hex().starts_with("000001dbbfa"));
+        assert!(leading_zero_nibbles(&digest, 5));
+        assert!(!leading_zero_nibbles(&digest, 6));
+
+        let zeroes = [0u8; 16];
+        assert!(leading_zero_nibbles(&zeroes, 0));
+        assert!(leading_zero_nibbles(&zeroes, 31));
+        assert!(leading_zero_nibbles(&zeroes, 32));
+        assert!(!leading_zero_nibbles(&zeroes, 33));
+        assert!(!leading_zero_nibbles(&zeroes, 100));
+    }
+
+    #[test]
+    fn test_search_window() {
+        let found: Vec<_> = Md5Search::new("abcdef")
+            .start(609_000)
+            .end(610_000)
+            .chunk_size(100)
+            .search(|d| leading_zero_nibbles(d, 5))
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(found, vec![609043]);
+    }
+
+    #[test]
+    fn test_custom_suffix() {
+        let search = Md5Search::new("abc").suffix(|n, buf: &mut Vec<u8>| {
+            buf.extend_from_slice(b"x-");
+            write_decimal(n, buf);
+        });
+        let mut context = md5::Context::new();
+        context.consume("abcx-12");
+        assert_eq!(search.digest(12), context.finalize().0);
+    }
+
+    #[test]
+    fn test_long_suffix() {
+        let search = Md5Search::new("abc").suffix(|n, buf: &mut Vec<u8>| {
+            buf.resize(100, b'-');
+            write_decimal(n, buf);
+        });
+        let mut context = md5::Context::new();
+        context.consume(format!("abc{}7", "-".repeat(100)));
+        let expected = context.finalize().0;
+        assert_eq!(search.digest(7), expected);
+
+        let found: Vec<_> = search.end(50).chunk_size(7).search(|_| true).collect();
+        assert_eq!(found.len(), 50);
+        assert_eq!(found[7], (7, expected));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        token.cancel();
+        let mut found = Md5Search::new("abc").cancel_token(token).search(|_| true);
+        assert_eq!(found.next(), None);
+    }
+
+    #[test]
+    fn test_cancel_mid_chunk() {
+        use std::sync::atomic::AtomicUsize;
+
+        // cancels while testing the third chunk, which is dropped as a whole.
+        let token = CancelToken::new();
+        let tested = AtomicUsize::new(0);
+        let found: Vec<_> = Md5Search::new("abc")
+            .chunk_size(10)
+            .cancel_token(token.clone())
+            .search(|_| {
+                if tested.fetch_add(1, Ordering::Relaxed) == 24 {
+                    token.cancel();
+                }
+                true
+            })
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(found, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_hash_stream() {
+        let mut stream: HashStream = HashStream::new("abc", 0).batch_size(100);
+        assert_eq!(stream.get(18).to_hex()[..12], *"0034e0923cc3");
+        assert_eq!(stream.get(18), stretched_digest::<Md5>("abc", 0, 18));
+
+        let keys: Vec<usize> = (0..)
+            .filter(|&n| stream.confirmed_run(n, 3, 5, 1000).is_some())
+            .take(64)
+            .collect();
+        assert_eq!(keys[..2], [39, 92]);
+        assert_eq!(keys[63], 22728);
+    }
+
+    #[test]
+    fn test_stretched_stream() {
+        let mut stream: HashStream = HashStream::new("abc", 2016).batch_size(4);
+        assert_eq!(stream.get(0).to_hex(), "a107ff634856bb300138cac6568c0f24");
+        assert_eq!(stream.window(0, 4).count(), 4);
+    }
+
+    #[test]
+    fn test_other_digests() {
+        let search = DigestSearch::<Sha256>::new("abc").suffix(|_, _: &mut Vec<u8>| {});
+        assert_eq!(search.digest(0), Sha256::digest("abc"));
+
+        let found: Vec<_> = DigestSearch::<KnotHash>::new("flqrgnkx-")
+            .end(8)
+            .search(|d| d[0] & 0xc0 == 0xc0)
+            .map(|(n, _)| n)
+            .collect();
+        // Rows of the 2017 day 14 example grid starting "##"
+        assert_eq!(found, vec![0, 5, 7]);
     }
 }
@@ -1,7 +1,14 @@
+use crate::Pos;
+use std::fmt::{Display, Formatter};
 use std::iter::Peekable;
 
 pub trait NibbleIter {
     fn nibbles(&self) -> Nibbles<'_>;
+
+    /// Lowercase hex string, two characters per byte.
+    fn to_hex(&self) -> String {
+        hex_string(self.nibbles())
+    }
 }
 
 impl NibbleIter for [u8] {
@@ -26,7 +33,7 @@ impl<'a> Iterator for Nibbles<'a> {
         if byte_idx >= self.bytes.len() {
             return None;
         }
-        let nibble = if self.index % 2 == 0 {
+        let nibble = if self.index.is_multiple_of(2) {
             self.bytes[byte_idx] / 16
         } else {
             self.bytes[byte_idx] % 16
@@ -36,8 +43,99 @@ impl<'a> Iterator for Nibbles<'a> {
     }
 }
 
+/// The ASCII lowercase hex digit for a nibble (`0..16`).
+#[inline]
+pub fn hex_digit(nibble: u8) -> u8 {
+    match nibble {
+        0..10 => b'0' + nibble,
+        10..16 => b'a' + nibble - 10,
+        _ => panic!("Invalid hex value"),
+    }
+}
+
+pub fn hex_string<I: IntoIterator<Item = u8>>(nibbles: I) -> String {
+    nibbles.into_iter().map(|n| hex_digit(n) as char).collect()
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Iterates over bits, most significant bit first.
+pub trait BitIter {
+    type Bits: Iterator<Item = bool>;
+
+    fn bits(self) -> Self::Bits;
+}
+
+impl<'a> BitIter for &'a [u8] {
+    type Bits = Bits<'a>;
+
+    fn bits(self) -> Self::Bits {
+        Bits {
+            bytes: self,
+            index: 0,
+        }
+    }
+}
+
+impl BitIter for u64 {
+    type Bits = WordBits;
+
+    fn bits(self) -> Self::Bits {
+        WordBits {
+            word: self,
+            remaining: u64::BITS,
+        }
+    }
+}
+
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> Iterator for Bits<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = self.bytes.get(self.index / 8)?;
+        let bit = byte & (0x80 >> (self.index % 8)) != 0;
+        self.index += 1;
+        Some(bit)
+    }
+}
+
+pub struct WordBits {
+    word: u64,
+    remaining: u32,
+}
+
+impl Iterator for WordBits {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.word & (1 << self.remaining) != 0)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
 pub trait RepeatedRuns: Iterator<Item = u8> + Sized {
     fn repeated(self, n: usize) -> Repeated<Self>;
+
+    /// Run-length encodes the values as `(value, start, len)`.
+    fn runs(self) -> Runs<Self>;
+
+    fn first_run_at_least(self, n: usize) -> Option<(u8, usize, usize)> {
+        self.runs().find(|&(_, _, len)| len >= n)
+    }
+
+    fn first_run_exactly(self, n: usize) -> Option<(u8, usize, usize)> {
+        self.runs().find(|&(_, _, len)| len == n)
+    }
 }
 
 impl<I: Iterator<Item = u8>> RepeatedRuns for I {
@@ -47,6 +145,13 @@ impl<I: Iterator<Item = u8>> RepeatedRuns for I {
             min_length: n,
         }
     }
+
+    fn runs(self) -> Runs<Self> {
+        Runs {
+            iter: self.peekable(),
+            index: 0,
+        }
+    }
 }
 
 pub struct Repeated<I: Iterator<Item = u8>> {
@@ -71,3 +176,209 @@ impl<I: Iterator<Item = u8>> Iterator for Repeated<I> {
         None
     }
 }
+
+pub struct Runs<I: Iterator<Item = u8>> {
+    iter: Peekable<I>,
+    index: usize,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Runs<I> {
+    type Item = (u8, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let val = self.iter.next()?;
+        let start = self.index;
+        let mut len = 1;
+        while self.iter.next_if_eq(&val).is_some() {
+            len += 1;
+        }
+        self.index += len;
+        Some((val, start, len))
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A fixed size grid of on/off pixels, packed 64 to a word. Renders with `#`
+/// for on and `.` for off, which is the format `advent-of-code-ocr` reads.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    /// Reads a grid of characters, where `on` marks a set pixel.
+    pub fn parse(input: &str, on: char) -> Self {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = Self::new(width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                if c == on {
+                    grid.set(&(x as isize, y as isize), true);
+                }
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Out of bounds pixels are always off.
+    pub fn get(&self, pos: &Pos) -> bool {
+        self.locate(pos)
+            .is_some_and(|(i, mask)| self.words[i] & mask != 0)
+    }
+
+    pub fn set(&mut self, pos: &Pos, on: bool) {
+        let (i, mask) = self.locate(pos).expect("Position out of bounds");
+        if on {
+            self.words[i] |= mask;
+        } else {
+            self.words[i] &= !mask;
+        }
+    }
+
+    pub fn toggle(&mut self, pos: &Pos) {
+        let (i, mask) = self.locate(pos).expect("Position out of bounds");
+        self.words[i] ^= mask;
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width)
+                .map(move |x| (x as isize, y as isize))
+                .filter(|pos| self.get(pos))
+        })
+    }
+
+    /// Shifts row `y` right by `n`, wrapping pixels around to the start.
+    pub fn rotate_row(&mut self, y: usize, n: usize) {
+        let row: Vec<bool> = (0..self.width).map(|x| self.get(&pos(x, y))).collect();
+        for (x, on) in row.into_iter().enumerate() {
+            self.set(&pos((x + n) % self.width, y), on);
+        }
+    }
+
+    /// Shifts column `x` down by `n`, wrapping pixels around to the top.
+    pub fn rotate_column(&mut self, x: usize, n: usize) {
+        let col: Vec<bool> = (0..self.height).map(|y| self.get(&pos(x, y))).collect();
+        for (y, on) in col.into_iter().enumerate() {
+            self.set(&pos(x, (y + n) % self.height), on);
+        }
+    }
+
+    fn locate(&self, pos: &Pos) -> Option<(usize, u64)> {
+        let (x, y) = (usize::try_from(pos.0).ok()?, usize::try_from(pos.1).ok()?);
+        (x < self.width && y < self.height)
+            .then(|| (y * self.words_per_row + x / 64, 1 << (x % 64)))
+    }
+}
+
+impl Display for BitGrid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for y in 0..self.height {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for x in 0..self.width {
+                write!(f, "{}", if self.get(&pos(x, y)) { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[inline]
+fn pos(x: usize, y: usize) -> Pos {
+    (x as isize, y as isize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!([0x0f_u8, 0xa2, 0x00].to_hex(), "0fa200");
+        assert_eq!(hex_string([1, 11, 15]), "1bf");
+    }
+
+    #[test]
+    fn test_bits() {
+        let bytes: &[u8] = &[0b1010_0000, 0xff];
+        let bits: Vec<bool> = bytes.bits().collect();
+        assert_eq!(bits.len(), 16);
+        assert_eq!(&bits[..4], &[true, false, true, false]);
+        assert!(bits[8..].iter().all(|&b| b));
+
+        let bits: Vec<bool> = 5_u64.bits().collect();
+        assert_eq!(bits.len(), 64);
+        assert_eq!(&bits[61..], &[true, false, true]);
+    }
+
+    #[test]
+    fn test_runs() {
+        let runs: Vec<_> = [1, 1, 2, 3, 3, 3, 1].into_iter().runs().collect();
+        assert_eq!(runs, vec![(1, 0, 2), (2, 2, 1), (3, 3, 3), (1, 6, 1)]);
+    }
+
+    #[test]
+    fn test_first_run() {
+        let values = [7, 7, 7, 7, 4, 4, 4, 9];
+        assert_eq!(values.into_iter().first_run_at_least(3), Some((7, 0, 4)));
+        assert_eq!(values.into_iter().first_run_exactly(3), Some((4, 4, 3)));
+        assert_eq!(values.into_iter().first_run_exactly(5), None);
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let mut grid = BitGrid::new(70, 3);
+        grid.set(&(0, 0), true);
+        grid.set(&(65, 1), true);
+        grid.toggle(&(2, 2));
+        assert!(grid.get(&(65, 1)));
+        assert!(!grid.get(&(64, 1)));
+        assert!(!grid.get(&(-1, 0)));
+        assert_eq!(grid.count_ones(), 3);
+
+        grid.rotate_row(1, 10);
+        assert_eq!(
+            grid.ones().collect::<Vec<_>>(),
+            vec![(0, 0), (5, 1), (2, 2)]
+        );
+        grid.rotate_column(0, 1);
+        assert!(grid.get(&(0, 1)));
+    }
+
+    #[test]
+    fn test_bit_grid_display() {
+        let input = "#.#\n.#.\n##.";
+        let grid = BitGrid::parse(input, '#');
+        assert_eq!(grid.count_ones(), 5);
+        assert_eq!(grid.to_string(), input);
+    }
+}
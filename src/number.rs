@@ -0,0 +1,188 @@
+use num::{Integer, Signed};
+
+/// Extended Euclid. Returns `(g, x, y)` such that `a*x + b*y = g = gcd(a, b)`,
+/// with `g` non-negative.
+pub fn ext_gcd<T: Integer + Signed + Copy>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+
+    while !r.is_zero() {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r.is_negative() {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m` (in `0..m`), if `a` and `m` are coprime.
+pub fn mod_inv<T: Integer + Signed + Copy>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = ext_gcd(a.mod_floor(&m), m);
+    g.is_one().then(|| x.mod_floor(&m))
+}
+
+/// `base^exp mod m` by repeated squaring. Intermediate products are up to
+/// `m^2`, so use a wider type (e.g. `u128`) for moduli beyond 32 bits.
+pub fn mod_pow<T: Integer + Copy>(base: T, mut exp: T, m: T) -> T {
+    let two = T::one() + T::one();
+    let mut base = base.mod_floor(&m);
+    let mut result = T::one().mod_floor(&m);
+
+    while exp > T::zero() {
+        if exp.is_odd() {
+            result = (result * base).mod_floor(&m);
+        }
+        base = (base * base).mod_floor(&m);
+        exp = exp / two;
+    }
+    result
+}
+
+/// Least common multiple of every value (`1` if there are none).
+pub fn lcm_all<T: Integer + Copy, I: IntoIterator<Item = T>>(values: I) -> T {
+    values.into_iter().fold(T::one(), |acc, v| acc.lcm(&v))
+}
+
+/// Chinese remainder theorem: finds `x` such that `x ≡ r (mod m)` for every
+/// `(r, m)`. The moduli need not be coprime. Returns `(x, lcm of moduli)` with
+/// `x` in `0..lcm`, or `None` if the congruences contradict each other.
+///
+/// ```
+/// // Buses 7, 13 and 59 leaving at offsets 0, 1 and 4
+/// let (t, _) = crt(&[(0, 7), (-1, 13), (-4, 59)]).unwrap();
+/// assert_eq!(t, 350);
+/// ```
+pub fn crt<T: Integer + Signed + Copy>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    congruences
+        .iter()
+        .try_fold((T::zero(), T::one()), |(r1, m1), &(r2, m2)| {
+            let (g, p, _) = ext_gcd(m1, m2);
+            let diff = r2 - r1;
+            if !diff.is_multiple_of(&g) {
+                return None;
+            }
+            let lcm = m1 / g * m2;
+            let k = (diff / g * p).mod_floor(&(m2 / g));
+            Some(((r1 + m1 * k).mod_floor(&lcm), lcm))
+        })
+}
+
+/// Prime factorisation by trial division, as `(prime, exponent)` pairs in
+/// ascending order of prime. Both `0` and `1` have no prime factors.
+pub fn prime_factors(mut n: u64) -> Vec<(u64, u32)> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut factors = vec![];
+    let mut take = |n: &mut u64, p: u64| {
+        let mut e = 0;
+        while n.is_multiple_of(p) {
+            *n /= p;
+            e += 1;
+        }
+        if e > 0 {
+            factors.push((p, e));
+        }
+    };
+
+    take(&mut n, 2);
+    take(&mut n, 3);
+    let mut p = 5;
+    while p <= n / p {
+        take(&mut n, p);
+        take(&mut n, p + 2);
+        p += 6;
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// Every divisor of `n` (including `1` and `n`), in ascending order.
+pub fn divisors(n: u64) -> Vec<u64> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut divisors = vec![1];
+    for (p, e) in prime_factors(n) {
+        let existing = divisors.len();
+        let mut pk = 1;
+        for _ in 0..e {
+            pk *= p;
+            divisors.extend_from_within(..existing);
+            let len = divisors.len();
+            divisors[(len - existing)..]
+                .iter_mut()
+                .for_each(|d| *d *= pk);
+        }
+    }
+    divisors.sort_unstable();
+    divisors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ext_gcd() {
+        let (g, x, y) = ext_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(ext_gcd(-4, 6).0, 2);
+    }
+
+    #[test]
+    fn test_mod_inv() {
+        assert_eq!(mod_inv(3, 11), Some(4));
+        assert_eq!(mod_inv(-3, 11), Some(7));
+        assert_eq!(mod_inv(6, 9), None);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(7_u128, 20201227 - 2, 20201227), 14429448);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_lcm_all() {
+        assert_eq!(lcm_all([4, 6, 10]), 60);
+        assert_eq!(lcm_all(Vec::<u64>::new()), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(
+            crt(&[(0_i64, 17), (-2, 13), (-3, 19)]),
+            Some((3417, 17 * 13 * 19))
+        );
+        // Non-coprime moduli
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(prime_factors(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(prime_factors(1), vec![]);
+        assert_eq!(prime_factors(0), vec![]);
+        assert_eq!(prime_factors(600851475143).last(), Some(&(6857, 1)));
+    }
+
+    #[test]
+    fn test_divisors() {
+        assert_eq!(divisors(12), vec![1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1), vec![1]);
+        assert_eq!(divisors(0), vec![]);
+    }
+}
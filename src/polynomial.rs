@@ -0,0 +1,224 @@
+use num::rational::BigRational;
+use num::{BigInt, One, Zero};
+use std::error::Error;
+use std::fmt::Display;
+
+/// A polynomial with exact rational coefficients, lowest degree first.
+///
+/// Built from equally spaced samples using Newton forward differences, so the
+/// usual "the answer at step 26501365 follows a quadratic" puzzles can be
+/// extrapolated without the float drift of `polynomial_interpolate`.
+///
+/// ```
+/// // f(x) = x^2 + x + 1 sampled at x = 65, 196, 327
+/// let p = Polynomial::fit(65, 131, &[4291, 38613, 107257]).unwrap();
+/// assert_eq!(p.eval_integer::<i64>(26501365), Some(702322373364591));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<BigRational>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PolynomialError {
+    NoSamples,
+    ZeroStep,
+    NotPolynomial,
+}
+
+impl Error for PolynomialError {}
+
+impl Display for PolynomialError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolynomialError::NoSamples => write!(f, "no samples to fit."),
+            PolynomialError::ZeroStep => write!(f, "samples must be at distinct x values."),
+            PolynomialError::NotPolynomial => {
+                write!(f, "samples do not settle into a constant difference.")
+            }
+        }
+    }
+}
+
+impl Polynomial {
+    /// Fits the unique polynomial of degree `< ys.len()` passing through
+    /// `(x0 + i * step, ys[i])` for every sample.
+    pub fn fit<T: Copy + Into<BigInt>>(x0: T, step: T, ys: &[T]) -> Result<Self, PolynomialError> {
+        let (x0, step): (BigInt, BigInt) = (x0.into(), step.into());
+        if ys.is_empty() {
+            return Err(PolynomialError::NoSamples);
+        }
+        if step.is_zero() {
+            return Err(PolynomialError::ZeroStep);
+        }
+
+        let leading = forward_differences(ys)
+            .into_iter()
+            .map(|row| BigRational::from_integer(row[0].clone()));
+
+        // p(x) = Σ Δ^k y0 * C(u, k) where u = (x - x0) / step. Each binomial
+        // term is built from the previous one by multiplying by (u - k) / (k + 1).
+        let mut coefficients = vec![BigRational::zero()];
+        let mut basis = vec![BigRational::one()];
+        for (k, delta) in leading.enumerate() {
+            add_scaled(&mut coefficients, &basis, &delta);
+            let denom = BigRational::from_integer(&step * BigInt::from(k + 1));
+            let offset = BigRational::from_integer(&x0 + &step * BigInt::from(k));
+            basis = mul_linear(&basis, &(-offset / &denom), &(BigRational::one() / denom));
+        }
+
+        while coefficients.len() > 1 && coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+        Ok(Self { coefficients })
+    }
+
+    /// Like `fit`, but first requires the samples to confirm their degree
+    /// (see `detect_degree`), and then only uses as many as that needs.
+    pub fn detect<T: Copy + Into<BigInt>>(
+        x0: T,
+        step: T,
+        ys: &[T],
+    ) -> Result<Self, PolynomialError> {
+        let degree = detect_degree(ys).ok_or(if ys.is_empty() {
+            PolynomialError::NoSamples
+        } else {
+            PolynomialError::NotPolynomial
+        })?;
+        Self::fit(x0, step, &ys[..=degree])
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len() - 1
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    pub fn eval<T: Into<BigInt>>(&self, x: T) -> BigRational {
+        let x = BigRational::from_integer(x.into());
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |acc, c| acc * &x + c)
+    }
+
+    /// Evaluates at `x`, returning `None` if the result is not an integer or
+    /// does not fit in `U`.
+    pub fn eval_integer<U: TryFrom<BigInt>>(&self, x: impl Into<BigInt>) -> Option<U> {
+        let y = self.eval(x);
+        if y.is_integer() {
+            U::try_from(y.to_integer()).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// The degree of the polynomial generating `ys` (sampled at equally spaced
+/// points), i.e. the first level of forward differences that is constant.
+/// At least two equal values are needed at that level to confirm it, so
+/// `None` is returned if the samples run out first.
+pub fn detect_degree<T: Copy + Into<BigInt>>(ys: &[T]) -> Option<usize> {
+    forward_differences(ys)
+        .iter()
+        .position(|row| row.len() >= 2 && row.iter().all(|d| *d == row[0]))
+}
+
+/// Rows of the forward difference table: `ys`, `Δys`, `Δ²ys`, ...
+fn forward_differences<T: Copy + Into<BigInt>>(ys: &[T]) -> Vec<Vec<BigInt>> {
+    let mut rows: Vec<Vec<BigInt>> = vec![ys.iter().map(|&y| y.into()).collect()];
+    while rows.last().is_some_and(|row| row.len() > 1) {
+        let row = rows.last().unwrap();
+        let next = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        rows.push(next);
+    }
+    rows
+}
+
+fn add_scaled(acc: &mut Vec<BigRational>, p: &[BigRational], scale: &BigRational) {
+    if acc.len() < p.len() {
+        acc.resize(p.len(), BigRational::zero());
+    }
+    for (a, c) in acc.iter_mut().zip(p) {
+        *a += c * scale;
+    }
+}
+
+/// Multiplies `p` by `(a + b x)`.
+fn mul_linear(p: &[BigRational], a: &BigRational, b: &BigRational) -> Vec<BigRational> {
+    let mut result = vec![BigRational::zero(); p.len() + 1];
+    for (i, c) in p.iter().enumerate() {
+        result[i] += c * a;
+        result[i + 1] += c * b;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(n: i64, d: i64) -> BigRational {
+        BigRational::new(n.into(), d.into())
+    }
+
+    #[test]
+    fn test_fit_sequence() {
+        // 3n^2 - 2n + 7
+        let p = Polynomial::fit(0, 1, &[7, 8, 15, 28]).unwrap();
+        assert_eq!(p.degree(), 2);
+        assert_eq!(p.coefficients(), &[ratio(7, 1), ratio(-2, 1), ratio(3, 1)]);
+        assert_eq!(p.eval_integer::<i64>(10), Some(287));
+    }
+
+    #[test]
+    fn test_fit_spaced_samples() {
+        // x^2 / 2 + x / 2 at x = 65, 196, 327
+        let p = Polynomial::fit(65, 131, &[2145, 19306, 53628]).unwrap();
+        assert_eq!(p.coefficients(), &[ratio(0, 1), ratio(1, 2), ratio(1, 2)]);
+        assert_eq!(p.eval_integer::<i128>(26501365), Some(351161186682295));
+        assert_eq!(p.eval(2), ratio(3, 1));
+    }
+
+    #[test]
+    fn test_non_integer_result() {
+        let p = Polynomial::fit(0, 2, &[0, 1]).unwrap();
+        assert_eq!(p.eval(1), ratio(1, 2));
+        assert_eq!(p.eval_integer::<i64>(1), None);
+    }
+
+    #[test]
+    fn test_detect_degree() {
+        assert_eq!(detect_degree(&[5, 5, 5]), Some(0));
+        assert_eq!(detect_degree(&[1, 3, 5, 7]), Some(1));
+        assert_eq!(detect_degree(&[0, 3, 6, 9, 12, 15]), Some(1));
+        assert_eq!(detect_degree(&[1, 4, 9, 16]), Some(2));
+        assert_eq!(detect_degree(&[1, 4, 9]), None);
+        assert_eq!(detect_degree(&[1, 2, 4, 8, 16, 32]), None);
+    }
+
+    #[test]
+    fn test_detect() {
+        let p = Polynomial::detect(0, 1, &[0, 1, 4, 9, 16, 25]).unwrap();
+        assert_eq!(p.degree(), 2);
+        assert_eq!(p.eval_integer::<i64>(1000), Some(1_000_000));
+    }
+
+    #[test]
+    fn test_degenerate_input() {
+        assert_eq!(
+            Polynomial::fit::<i64>(0, 1, &[]),
+            Err(PolynomialError::NoSamples)
+        );
+        assert_eq!(
+            Polynomial::fit(3, 0, &[1, 2]),
+            Err(PolynomialError::ZeroStep)
+        );
+        assert_eq!(
+            Polynomial::detect(0, 1, &[1, 2, 4, 8]),
+            Err(PolynomialError::NotPolynomial)
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Links every day into the main binary, so that `all` and `time` can run them in-process.
+//! Enabled by the `registry` feature. Generated by `cargo scaffold`, do not edit.
+#![allow(dead_code)]
+
+use advent_of_code::template::runner::Registry;
+
+// every day brings its own global allocator when profiling.
+#[cfg(feature = "dhat-heap")]
+compile_error!(
+    "the `registry` feature can't be combined with `dhat-heap`, profile days with `cargo solve --dhat`."
+);
+
+#[path = "bin/01.rs"]
+mod day01;
+
+#[path = "bin/02.rs"]
+mod day02;
+
+#[path = "bin/03.rs"]
+mod day03;
+
+#[path = "bin/04.rs"]
+mod day04;
+
+#[path = "bin/05.rs"]
+mod day05;
+
+#[path = "bin/06.rs"]
+mod day06;
+
+#[path = "bin/07.rs"]
+mod day07;
+
+#[path = "bin/08.rs"]
+mod day08;
+
+#[path = "bin/09.rs"]
+mod day09;
+
+#[path = "bin/10.rs"]
+mod day10;
+
+#[path = "bin/11.rs"]
+mod day11;
+
+pub fn registry() -> Registry {
+    Registry::new(vec![
+        (advent_of_code::day!(1), day01::run_day),
+        (advent_of_code::day!(2), day02::run_day),
+        (advent_of_code::day!(3), day03::run_day),
+        (advent_of_code::day!(4), day04::run_day),
+        (advent_of_code::day!(5), day05::run_day),
+        (advent_of_code::day!(6), day06::run_day),
+        (advent_of_code::day!(7), day07::run_day),
+        (advent_of_code::day!(8), day08::run_day),
+        (advent_of_code::day!(9), day09::run_day),
+        (advent_of_code::day!(10), day10::run_day),
+        (advent_of_code::day!(11), day11::run_day),
+    ])
+}
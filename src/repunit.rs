@@ -0,0 +1,197 @@
+use crate::{digit_count, divisors, prime_factors};
+
+/// How many times a block of digits must repeat to make up a number, e.g.
+/// `123123` is `123` repeated exactly 2 times, and `111111` is a block
+/// repeated 2, 3 or 6 times. Every positive number is itself repeated once,
+/// so `Exactly(1)` and `AtLeast(1)` match them all.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Repeats {
+    Exactly(u32),
+    AtLeast(u32),
+}
+
+/// `(10^(k*len) - 1) / (10^len - 1)`, i.e. `1`, `0..01` repeated. Multiplying
+/// a `len` digit block by it repeats that block `k` times: `123 * 1001001 = 123123123`.
+pub fn repunit_multiplier(len: u32, k: u32) -> u128 {
+    (10_u128.pow(k * len) - 1) / (10_u128.pow(len) - 1)
+}
+
+/// Every number in `lo..=hi` made of a block of digits repeated as required,
+/// in ascending order. These are generated directly from the multipliers
+/// rather than testing each number in the range.
+pub fn repeated_numbers(lo: u64, hi: u64, repeats: Repeats) -> Vec<u64> {
+    let mut numbers: Vec<u64> = number_lengths(lo, hi)
+        .flat_map(|len| {
+            block_lengths(len, repeats).flat_map(move |block| {
+                let m = repunit_multiplier(block, len / block);
+                let (a, b) = block_range(lo, hi, block, m);
+                (a..=b).map(move |n| (n * m) as u64)
+            })
+        })
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+/// How many numbers `repeated_numbers` would return, computed in closed form.
+pub fn repeated_count(lo: u64, hi: u64, repeats: Repeats) -> u64 {
+    repeated_totals(lo, hi, repeats).0 as u64
+}
+
+/// The sum of the numbers `repeated_numbers` would return, computed in
+/// closed form without iterating over them.
+pub fn repeated_sum(lo: u64, hi: u64, repeats: Repeats) -> u128 {
+    repeated_totals(lo, hi, repeats).1
+}
+
+/// `(count, sum)` of the matching numbers.
+///
+/// For `Exactly(k)` a number matches iff its block length `len / k` is one of
+/// its periods. For `AtLeast(k)` a number matches iff its *smallest* period
+/// `p` satisfies `len / p >= k`. Numbers with smallest period exactly `p` are
+/// counted by Möbius inversion over the numbers having period `d` for each
+/// `d` dividing `p`. This stops numbers like `111111` being counted once for
+/// each of their periods.
+fn repeated_totals(lo: u64, hi: u64, repeats: Repeats) -> (i128, u128) {
+    let mut count = 0;
+    let mut sum = 0;
+    for len in number_lengths(lo, hi) {
+        match repeats {
+            Repeats::Exactly(k) => {
+                if k > 0 && len.is_multiple_of(k) {
+                    let (c, s) = period_totals(lo, hi, len, len / k);
+                    count += c;
+                    sum += s;
+                }
+            }
+            Repeats::AtLeast(_) => {
+                for p in block_lengths(len, repeats) {
+                    for d in divisors(p as u64) {
+                        let mu = mobius(p as u64 / d);
+                        let (c, s) = period_totals(lo, hi, len, d as u32);
+                        count += mu * c;
+                        sum += mu * s;
+                    }
+                }
+            }
+        }
+    }
+    (count, sum as u128)
+}
+
+/// `(count, sum)` of the `len` digit numbers in `lo..=hi` which are a `block`
+/// digit number repeated.
+fn period_totals(lo: u64, hi: u64, len: u32, block: u32) -> (i128, i128) {
+    let m = repunit_multiplier(block, len / block);
+    let (a, b) = block_range(lo, hi, block, m);
+    if a > b {
+        return (0, 0);
+    }
+    let n = b - a + 1;
+    (n as i128, (m * (a + b) * n / 2) as i128)
+}
+
+/// The blocks of `block` digits which, once repeated using multiplier `m`,
+/// land in `lo..=hi`.
+fn block_range(lo: u64, hi: u64, block: u32, m: u128) -> (u128, u128) {
+    let a = (lo as u128).div_ceil(m).max(10_u128.pow(block - 1));
+    let b = (hi as u128 / m).min(10_u128.pow(block) - 1);
+    (a, b)
+}
+
+fn number_lengths(lo: u64, hi: u64) -> impl Iterator<Item = u32> {
+    let first = digit_count(lo.max(1), 10) as u32;
+    let last = if hi < lo {
+        0
+    } else {
+        digit_count(hi, 10) as u32
+    };
+    first..=last
+}
+
+/// Candidate block lengths for a number of `len` digits.
+fn block_lengths(len: u32, repeats: Repeats) -> impl Iterator<Item = u32> {
+    divisors(len as u64)
+        .into_iter()
+        .map(|d| d as u32)
+        .filter(move |&block| match repeats {
+            Repeats::Exactly(k) => block * k == len,
+            Repeats::AtLeast(k) => len / block >= k,
+        })
+}
+
+fn mobius(n: u64) -> i128 {
+    let factors = prime_factors(n);
+    if factors.iter().any(|&(_, e)| e > 1) {
+        0
+    } else if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(lo: u64, hi: u64, repeats: Repeats) -> Vec<u64> {
+        (lo.max(1)..=hi)
+            .filter(|n| {
+                let s = n.to_string();
+                (1..=s.len()).any(|block| {
+                    let k = (s.len() / block) as u32;
+                    s.len().is_multiple_of(block)
+                        && s == s[..block].repeat(k as usize)
+                        && match repeats {
+                            Repeats::Exactly(want) => k == want,
+                            Repeats::AtLeast(want) => k >= want,
+                        }
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_repunit_multiplier() {
+        assert_eq!(repunit_multiplier(3, 3), 1001001);
+        assert_eq!(repunit_multiplier(1, 4), 1111);
+        assert_eq!(123 * repunit_multiplier(3, 2), 123123);
+    }
+
+    #[test]
+    fn test_repeated_numbers() {
+        assert_eq!(
+            repeated_numbers(95, 115, Repeats::AtLeast(2)),
+            vec![99, 111]
+        );
+        assert_eq!(repeated_numbers(95, 115, Repeats::Exactly(2)), vec![99]);
+        assert_eq!(
+            repeated_numbers(1188511880, 1188511890, Repeats::Exactly(2)),
+            vec![1188511885]
+        );
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        for (lo, hi) in [(1, 200_000), (998, 1012), (222220, 222224), (0, 0)] {
+            for repeats in [
+                Repeats::Exactly(1),
+                Repeats::Exactly(2),
+                Repeats::Exactly(3),
+                Repeats::AtLeast(1),
+                Repeats::AtLeast(2),
+                Repeats::AtLeast(3),
+            ] {
+                let expected = brute_force(lo, hi, repeats);
+                assert_eq!(repeated_numbers(lo, hi, repeats), expected);
+                assert_eq!(repeated_count(lo, hi, repeats), expected.len() as u64);
+                assert_eq!(
+                    repeated_sum(lo, hi, repeats),
+                    expected.iter().map(|&n| n as u128).sum()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::iter::FusedIterator;
+
+pub const DIGIT_NAMES: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const TEEN_NAMES: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS_NAMES: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// A digit found by `DigitScanner`: `len` bytes at byte offset `pos`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DigitMatch {
+    pub pos: usize,
+    pub len: usize,
+    pub value: u32,
+    pub spelled: bool,
+}
+
+/// Finds every digit in a string, written either as a numeral or (optionally)
+/// spelled out as "zero" to "nine". A match is tried at every position, so
+/// overlapping words like "oneight" give both `1` and `8`. Scans from either
+/// end, so the last digit is `scanner.next_back()`.
+///
+/// ```
+/// let digits: Vec<u32> = digit_scanner("xtwone3four", true).map(|m| m.value).collect();
+/// assert_eq!(digits, vec![2, 1, 3, 4]);
+/// ```
+pub struct DigitScanner<'a> {
+    s: &'a str,
+    front: usize,
+    back: usize,
+    spelled: bool,
+}
+
+impl DigitScanner<'_> {
+    fn match_at(&self, pos: usize) -> Option<DigitMatch> {
+        let rest = &self.s.as_bytes()[pos..];
+        let numeral = rest
+            .first()
+            .filter(|b| b.is_ascii_digit())
+            .map(|b| DigitMatch {
+                pos,
+                len: 1,
+                value: (b - b'0') as u32,
+                spelled: false,
+            });
+        numeral.or_else(|| {
+            if !self.spelled {
+                return None;
+            }
+            DIGIT_NAMES
+                .iter()
+                .position(|name| rest.starts_with(name.as_bytes()))
+                .map(|value| DigitMatch {
+                    pos,
+                    len: DIGIT_NAMES[value].len(),
+                    value: value as u32,
+                    spelled: true,
+                })
+        })
+    }
+}
+
+impl Iterator for DigitScanner<'_> {
+    type Item = DigitMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            let found = self.match_at(self.front);
+            self.front += 1;
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+}
+
+impl DoubleEndedIterator for DigitScanner<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            self.back -= 1;
+            if let Some(found) = self.match_at(self.back) {
+                return Some(found);
+            }
+        }
+        None
+    }
+}
+
+impl FusedIterator for DigitScanner<'_> {}
+
+pub fn digit_scanner(s: &str, spelled: bool) -> DigitScanner<'_> {
+    DigitScanner {
+        s,
+        front: 0,
+        back: s.len(),
+        spelled,
+    }
+}
+
+/// The first and last digits in `s` (the same digit if there's only one).
+pub fn first_last_digits(s: &str, spelled: bool) -> Option<(u32, u32)> {
+    let mut scanner = digit_scanner(s, spelled);
+    let first = scanner.next()?;
+    let last = scanner.next_back().unwrap_or(first);
+    Some((first.value, last.value))
+}
+
+/// The last word read by `parse_spelled_number` within a group of up to three
+/// digits, deciding which words may follow.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Word {
+    Start,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+}
+
+/// Parses a number written in words, e.g. "one hundred and twenty-three" or
+/// "four thousand and six", up to the millions. Case is ignored, as is "and".
+/// Returns `None` for anything else, including numerals and words in the
+/// wrong order: tens must come before units, a group has at most one of each,
+/// scales must decrease ("one two" and "one thousand two thousand" are not
+/// numbers) and "zero" stands alone.
+pub fn parse_spelled_number(s: &str) -> Option<u64> {
+    let mut total = 0;
+    let mut current = 0;
+    let mut last = Word::Start;
+    let mut last_scale = u64::MAX;
+    let mut seen = false;
+
+    let words = s
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|w| !w.is_empty())
+        .map(str::to_ascii_lowercase);
+    for word in words {
+        if word == "and" {
+            continue;
+        }
+        if seen && total + current == 0 {
+            // nothing may follow "zero".
+            return None;
+        }
+        if let Some(n) = small_number(&word) {
+            let kind = match n {
+                0 if seen => return None,
+                0..10 => Word::Unit,
+                10..20 => Word::Teen,
+                _ => Word::Tens,
+            };
+            let allowed = match kind {
+                Word::Unit => matches!(last, Word::Start | Word::Tens | Word::Hundred),
+                _ => matches!(last, Word::Start | Word::Hundred),
+            };
+            if !allowed {
+                return None;
+            }
+            seen = true;
+            current += n;
+            last = kind;
+            continue;
+        }
+        seen = true;
+        let scale = match word.as_str() {
+            "hundred" => {
+                if current == 0 || current >= 100 {
+                    return None;
+                }
+                current *= 100;
+                last = Word::Hundred;
+                continue;
+            }
+            "thousand" => 1_000,
+            "million" => 1_000_000,
+            _ => return None,
+        };
+        if current == 0 || scale >= last_scale {
+            return None;
+        }
+        total += current * scale;
+        current = 0;
+        last = Word::Start;
+        last_scale = scale;
+    }
+
+    seen.then_some(total + current)
+}
+
+/// "zero" to "ninety".
+fn small_number(word: &str) -> Option<u64> {
+    let position = |names: &[&str]| names.iter().position(|&name| name == word);
+    position(&DIGIT_NAMES)
+        .map(|n| n as u64)
+        .or_else(|| position(&TEEN_NAMES).map(|n| n as u64 + 10))
+        .or_else(|| position(&TENS_NAMES).map(|n| n as u64 * 10 + 20))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_matches() {
+        let matches: Vec<_> = digit_scanner("oneight", true).collect();
+        assert_eq!(
+            matches,
+            vec![
+                DigitMatch {
+                    pos: 0,
+                    len: 3,
+                    value: 1,
+                    spelled: true
+                },
+                DigitMatch {
+                    pos: 2,
+                    len: 5,
+                    value: 8,
+                    spelled: true
+                }
+            ]
+        );
+        assert_eq!(digit_scanner("oneight", false).count(), 0);
+    }
+
+    #[test]
+    fn test_reverse_scan() {
+        let values: Vec<_> = digit_scanner("a1b22three", true)
+            .rev()
+            .map(|m| (m.pos, m.value))
+            .collect();
+        assert_eq!(values, vec![(5, 3), (4, 2), (3, 2), (1, 1)]);
+
+        let mut scanner = digit_scanner("1two3", true);
+        assert_eq!(scanner.next().map(|m| m.value), Some(1));
+        assert_eq!(scanner.next_back().map(|m| m.value), Some(3));
+        assert_eq!(scanner.next().map(|m| m.value), Some(2));
+        assert_eq!(scanner.next_back(), None);
+    }
+
+    #[test]
+    fn test_calibration() {
+        let input = "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n\
+                     4nineeightseven2\nzoneight234\n7pqrstsixteen";
+        let sum: u32 = input
+            .lines()
+            .filter_map(|line| first_last_digits(line, true))
+            .map(|(first, last)| first * 10 + last)
+            .sum();
+        assert_eq!(sum, 281);
+        assert_eq!(first_last_digits("treb7uchet", false), Some((7, 7)));
+        assert_eq!(first_last_digits("none", false), None);
+    }
+
+    #[test]
+    fn test_parse_spelled_number() {
+        assert_eq!(parse_spelled_number("zero"), Some(0));
+        assert_eq!(parse_spelled_number("Seventeen"), Some(17));
+        assert_eq!(parse_spelled_number("one hundred twenty three"), Some(123));
+        assert_eq!(parse_spelled_number("four thousand and six"), Some(4006));
+        assert_eq!(
+            parse_spelled_number("nine hundred ninety-nine thousand nine hundred"),
+            Some(999_900)
+        );
+        assert_eq!(
+            parse_spelled_number("two million three hundred thousand"),
+            Some(2_300_000)
+        );
+        assert_eq!(parse_spelled_number("twelve hundred"), Some(1200));
+        assert_eq!(parse_spelled_number("hundred"), None);
+        assert_eq!(parse_spelled_number("12"), None);
+        assert_eq!(parse_spelled_number(""), None);
+    }
+
+    #[test]
+    fn test_parse_spelled_number_order() {
+        for words in [
+            "one two",
+            "twenty twenty",
+            "three twenty",
+            "twenty eleven",
+            "eleven two",
+            "one hundred two hundred",
+            "one hundred hundred",
+            "one million two million",
+            "one thousand two million",
+            "one thousand thousand",
+            "zero zero",
+            "zero one",
+            "one zero",
+            "twenty zero",
+            "one hundred and zero",
+        ] {
+            assert_eq!(parse_spelled_number(words), None, "{words}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/// Store of accepted answers, used to guard solved days against regressions.
+use std::convert::TryFrom;
+use std::{collections::HashMap, fs, io, path::Path, str::FromStr};
+use tinyjson::JsonValue;
+
+use crate::template::Day;
+
+static ANSWERS_DIR_PATH: &str = "./data/answers";
+
+#[must_use]
+pub fn get_path_for_answers(day: Day) -> String {
+    format!("{ANSWERS_DIR_PATH}/{day}.json")
+}
+
+/// The accepted answers for both parts of a day, stored in `data/answers/{day}.json`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+}
+
+impl Answers {
+    /// Rehydrate the answers for a day. If not present, returns empty answers.
+    pub fn read(day: Day) -> Result<Self, String> {
+        let path = get_path_for_answers(day);
+
+        if !Path::new(&path).exists() {
+            return Ok(Answers::default());
+        }
+
+        fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(Answers::try_from)
+            .map_err(|e| format!("{path}: {e}"))
+    }
+
+    /// Dehydrate the answers for a day to its JSON file.
+    pub fn store(&self, day: Day) -> Result<(), io::Error> {
+        fs::create_dir_all(ANSWERS_DIR_PATH)?;
+        let json = JsonValue::from(self);
+        let mut file = fs::File::create(get_path_for_answers(day))?;
+        json.format_to(&mut file)
+    }
+
+    /// Adds an accepted answer for a day to the store, keeping the answer to the other part.
+    pub fn record(day: Day, part: u8, answer: &str) -> Result<(), String> {
+        let mut answers = Answers::read(day)?;
+        answers.set(part, answer.to_string());
+        answers.store(day).map_err(|e| e.to_string())
+    }
+
+    pub fn get(&self, part: u8) -> Option<&str> {
+        match part {
+            1 => self.part_1.as_deref(),
+            2 => self.part_2.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, part: u8, answer: String) {
+        match part {
+            1 => self.part_1 = Some(answer),
+            2 => self.part_2 = Some(answer),
+            _ => {}
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.part_1.is_none() && self.part_2.is_none()
+    }
+}
+
+/// Outcome of checking a part's answer against the store.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The answer matches the accepted one.
+    Match,
+    /// The answer differs from the accepted one, or the part is no longer solved.
+    Mismatch {
+        expected: String,
+        actual: Option<String>,
+    },
+    /// There is no accepted answer to compare with.
+    Unknown,
+}
+
+impl Verdict {
+    /// Compares an answer with the accepted one, ignoring trailing whitespace.
+    pub fn of(expected: Option<&str>, actual: Option<&str>) -> Self {
+        match (expected, actual) {
+            (None, _) => Verdict::Unknown,
+            (Some(expected), Some(actual)) if expected.trim_end() == actual.trim_end() => {
+                Verdict::Match
+            }
+            (Some(expected), actual) => Verdict::Mismatch {
+                expected: expected.to_string(),
+                actual: actual.map(ToString::to_string),
+            },
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&Answers> for JsonValue {
+    fn from(value: &Answers) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        let to_json =
+            |answer: &Option<String>| answer.clone().map_or(JsonValue::Null, JsonValue::String);
+
+        map.insert("part_1".into(), to_json(&value.part_1));
+        map.insert("part_2".into(), to_json(&value.part_2));
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<String> for Answers {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+        let json = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?;
+
+        let answer = |key: &str| match json.get(key) {
+            None => Ok(None),
+            Some(v) if v.is_null() => Ok(None),
+            Some(v) => v
+                .get::<String>()
+                .cloned()
+                .map(Some)
+                .ok_or(format!("expected `json.{key}` to be null or string.")),
+        };
+
+        Ok(Answers {
+            part_1: answer("part_1")?,
+            part_2: answer("part_2")?,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Answers, Verdict};
+    use tinyjson::JsonValue;
+
+    #[test]
+    fn round_trips_answers() {
+        let answers = Answers {
+            part_1: Some("42".into()),
+            part_2: Some("#..#\n.##.".into()),
+        };
+        let json = JsonValue::from(&answers).stringify().unwrap();
+        assert_eq!(Answers::try_from(json).unwrap(), answers);
+    }
+
+    #[test]
+    fn handles_partial_answers() {
+        let answers = Answers::try_from(r#"{ "part_1": "7" }"#.to_string()).unwrap();
+        assert_eq!(answers.get(1), Some("7"));
+        assert_eq!(answers.get(2), None);
+        assert_eq!(answers.is_empty(), false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_malformed_answers() {
+        Answers::try_from(r#"{ "part_1": 7 }"#.to_string()).unwrap();
+    }
+
+    #[test]
+    fn compares_answers() {
+        assert_eq!(Verdict::of(Some("42"), Some("42")), Verdict::Match);
+        assert_eq!(Verdict::of(Some("a\nb\n"), Some("a\nb")), Verdict::Match);
+        assert_eq!(Verdict::of(None, Some("42")), Verdict::Unknown);
+        assert_eq!(
+            Verdict::of(Some("42"), Some("43")),
+            Verdict::Mismatch {
+                expected: "42".into(),
+                actual: Some("43".into())
+            }
+        );
+        assert_eq!(
+            Verdict::of(Some("42"), None),
+            Verdict::Mismatch {
+                expected: "42".into(),
+                actual: None
+            }
+        );
+    }
+}
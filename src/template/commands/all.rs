@@ -1,5 +1,15 @@
+use crate::template::limits::Limits;
+use crate::template::runner::Registry;
 use crate::template::{all_days, run_multi::run_multi};
 
-pub fn handle(is_release: bool) {
-    run_multi(&all_days().collect(), is_release, false);
+pub fn handle(is_release: bool, jobs: Option<usize>, limits: Limits, registry: Option<&Registry>) {
+    run_multi(
+        &all_days().collect(),
+        is_release,
+        false,
+        None,
+        jobs.unwrap_or(1),
+        limits,
+        registry,
+    );
 }
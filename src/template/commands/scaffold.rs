@@ -1,14 +1,25 @@
 use std::{
-    fs::{File, OpenOptions},
-    io::Write,
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::Path,
     process,
 };
 
-use crate::template::Day;
+use crate::template::run_multi::get_path_for_bin;
+use crate::template::{Day, all_days};
 
 const MODULE_TEMPLATE: &str =
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/template.txt"));
 
+const MANIFEST_TEMPLATE: &str = r#"{
+  "examples": [
+    { "file": "%DAY%.txt", "part_1": null, "part_2": null }
+  ]
+}
+"#;
+
+const REGISTRY_PATH: &str = "src/registry.rs";
+
 fn safe_create_file(path: &str) -> Result<File, std::io::Error> {
     OpenOptions::new().write(true).create_new(true).open(path)
 }
@@ -20,6 +31,7 @@ fn create_file(path: &str) -> Result<File, std::io::Error> {
 pub fn handle(day: Day) {
     let input_path = format!("data/inputs/{day}.txt");
     let example_path = format!("data/examples/{day}.txt");
+    let manifest_path = format!("data/examples/{day}.json");
     let module_path = format!("src/bin/{day}.rs");
 
     let mut file = match safe_create_file(&module_path) {
@@ -64,6 +76,86 @@ pub fn handle(day: Day) {
         }
     }
 
+    // keep an existing manifest: it holds the expected answers.
+    match safe_create_file(&manifest_path) {
+        Ok(mut file) => match file.write_all(
+            MANIFEST_TEMPLATE
+                .replace("%DAY%", &day.to_string())
+                .as_bytes(),
+        ) {
+            Ok(()) => {
+                println!("Created example manifest \"{}\"", &manifest_path);
+            }
+            Err(e) => {
+                eprintln!("Failed to write example manifest: {e}");
+                process::exit(1);
+            }
+        },
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+        Err(e) => {
+            eprintln!("Failed to create example manifest: {e}");
+            process::exit(1);
+        }
+    }
+
+    match write_registry() {
+        Ok(()) => {
+            println!("Added day to the registry \"{REGISTRY_PATH}\"");
+        }
+        Err(e) => {
+            eprintln!("Failed to write registry: {e}");
+            process::exit(1);
+        }
+    }
+
     println!("---");
     println!("🎄 Type `cargo solve {day}` to run your solution.");
 }
+
+/// Regenerates `src/registry.rs`, which links every scaffolded day into the main binary
+/// when built with the `registry` feature.
+pub fn write_registry() -> Result<(), std::io::Error> {
+    fs::write(REGISTRY_PATH, registry_source(&scaffolded_days()))
+}
+
+fn scaffolded_days() -> Vec<Day> {
+    all_days()
+        .filter(|day| Path::new(&get_path_for_bin(*day)).exists())
+        .collect()
+}
+
+fn registry_source(days: &[Day]) -> String {
+    let mut lines: Vec<String> = vec![
+        "//! Links every day into the main binary, so that `all` and `time` can run them in-process.".into(),
+        "//! Enabled by the `registry` feature. Generated by `cargo scaffold`, do not edit.".into(),
+        "#![allow(dead_code)]".into(),
+        String::new(),
+        "use advent_of_code::template::runner::Registry;".into(),
+        String::new(),
+        "// every day brings its own global allocator when profiling.".into(),
+        "#[cfg(feature = \"dhat-heap\")]".into(),
+        "compile_error!(".into(),
+        "    \"the `registry` feature can't be combined with `dhat-heap`, profile days with `cargo solve --dhat`.\"".into(),
+        ");".into(),
+    ];
+
+    for day in days {
+        lines.push(String::new());
+        lines.push(format!("#[path = \"bin/{day}.rs\"]"));
+        lines.push(format!("mod day{day};"));
+    }
+
+    lines.push(String::new());
+    lines.push("pub fn registry() -> Registry {".into());
+    lines.push("    Registry::new(vec![".into());
+    for day in days {
+        lines.push(format!(
+            "        (advent_of_code::day!({}), day{day}::run_day),",
+            day.into_inner()
+        ));
+    }
+    lines.push("    ])".into());
+    lines.push("}".into());
+
+    lines.join("\n") + "\n"
+}
@@ -1,8 +1,18 @@
-use std::process::{Command, Stdio};
+use std::process::{self, Command, Stdio};
 
 use crate::template::Day;
+use crate::template::limits::Limits;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>, quiet: bool) {
+pub fn handle(
+    day: Day,
+    release: bool,
+    dhat: bool,
+    submit_part: Option<u8>,
+    quiet: bool,
+    examples: bool,
+    params: &[String],
+    limits: Limits,
+) {
     let mut cmd_args = vec!["run".to_string()];
 
     if quiet {
@@ -24,6 +34,17 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>, quie
 
     cmd_args.push("--".to_string());
 
+    if examples {
+        cmd_args.push("--examples".to_string());
+    }
+
+    for param in params {
+        cmd_args.push("--param".to_string());
+        cmd_args.push(param.clone());
+    }
+
+    cmd_args.extend(limits.to_args());
+
     if let Some(submit_part) = submit_part {
         cmd_args.push("--submit".to_string());
         cmd_args.push(submit_part.to_string());
@@ -36,5 +57,10 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>, quie
         .spawn()
         .unwrap();
 
-    cmd.wait().unwrap();
+    let status = cmd.wait().unwrap();
+
+    // a failing example run (or a day stopped by a limit) should fail `cargo solve` too.
+    if !status.success() {
+        process::exit(status.code().unwrap_or(1));
+    }
 }
@@ -1,10 +1,31 @@
 use std::collections::HashSet;
+use std::path::Path;
+use std::process;
 
+use crate::template::history::{self, Provenance};
+use crate::template::limits::Limits;
 use crate::template::run_multi::run_multi;
+use crate::template::runner::Registry;
 use crate::template::timings::Timings;
 use crate::template::{Day, all_days, readme_benchmarks};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+/// How to compare a run with the stored history: `compare` is `Some(None)` to compare with the previous run,
+/// or `Some(Some(ref))` to compare with the latest run at a branch, tag or commit. `threshold` is in percent.
+pub struct Comparison {
+    pub compare: Option<Option<String>>,
+    pub threshold: f64,
+}
+
+pub fn handle(
+    day: Option<Day>,
+    run_all: bool,
+    store: bool,
+    budget: Option<u64>,
+    jobs: Option<usize>,
+    limits: Limits,
+    comparison: Comparison,
+    registry: Option<&Registry>,
+) {
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -21,7 +42,39 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    // days benched at the same time disturb each other's timings, so only do that when asked to.
+    let jobs = jobs.unwrap_or(1);
+    if jobs > 1 {
+        eprintln!("Benching {jobs} days at a time, timings may be less accurate.\n");
+    }
+
+    let mut timings = run_multi(&days_to_run, true, true, budget, jobs, limits, registry).unwrap();
+
+    let provenance = Provenance::current();
+    timings.record_history(&provenance);
+
+    let mut regressed = false;
+
+    if let Some(reference) = &comparison.compare {
+        // a ref that is not a branch or tag is taken as (a prefix of) a commit hash.
+        let commit = reference.as_deref().map(|reference| {
+            history::resolve_ref(Path::new(".git"), reference).unwrap_or(reference.to_string())
+        });
+
+        let against = match reference {
+            Some(reference) => format!("the latest run at {reference} on this host"),
+            None => "the previous run on this host".to_string(),
+        };
+
+        let (changes, missing) = history::compare(
+            &stored_timings,
+            &timings,
+            &provenance.host,
+            commit.as_deref(),
+        );
+        regressed =
+            history::print_comparison(&changes, &missing, &against, comparison.threshold / 100.0);
+    }
 
     if store {
         let merged_timings = stored_timings.merge(&timings);
@@ -37,4 +90,9 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
             }
         }
     }
+
+    // fail the command on regressions, e.g. when comparing with `main` in CI.
+    if regressed {
+        process::exit(1);
+    }
 }
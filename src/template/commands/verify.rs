@@ -0,0 +1,88 @@
+use std::process;
+
+use crate::template::answers::{Answers, Verdict, get_path_for_answers};
+use crate::template::limits::Limits;
+use crate::template::run_multi::child_commands;
+use crate::template::runner::Stage;
+use crate::template::{ANSI_BOLD, ANSI_RESET, Day, all_days};
+
+/// Runs solutions and compares their answers with the accepted ones in `data/answers`.
+/// Without a day, checks every day that has stored answers (or every day with `--all`).
+/// Exits with a non-zero status if any answer changed.
+pub fn handle(day: Option<Day>, run_all: bool) {
+    let days: Vec<Day> = match day {
+        Some(day) => vec![day],
+        None => all_days()
+            .filter(|day| run_all || Answers::read(*day).is_ok_and(|a| !a.is_empty()))
+            .collect(),
+    };
+
+    if days.is_empty() {
+        println!(
+            "No stored answers found. Record answers after submitting with `cargo solve <day> --submit <part>`."
+        );
+        return;
+    }
+
+    let mut matched = 0;
+    let mut failed = 0;
+
+    for (i, day) in days.into_iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+
+        println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+        println!("------");
+
+        let answers = match Answers::read(day) {
+            Ok(answers) => answers,
+            Err(e) => {
+                eprintln!("Could not read stored answers: {e}");
+                failed += 1;
+                continue;
+            }
+        };
+
+        let records = child_commands::run_solution(day, false, true, None, Limits::default())
+            .unwrap()
+            .records;
+
+        if records.is_empty() {
+            println!("Not solved.");
+        }
+
+        for part in 1..=2 {
+            let actual = records
+                .iter()
+                .find(|r| r.stage == Stage::Part(part))
+                .and_then(|r| r.answer.as_deref());
+
+            match Verdict::of(answers.get(part), actual) {
+                Verdict::Match => {
+                    matched += 1;
+                    println!("Verify {part}: ✔ matches stored answer");
+                }
+                Verdict::Mismatch { expected, actual } => {
+                    failed += 1;
+                    println!("Verify {part}: ✖ expected:");
+                    println!("{expected}");
+                    println!("got:");
+                    println!("{}", actual.as_deref().unwrap_or("✖ (no answer)"));
+                }
+                Verdict::Unknown => {
+                    println!(
+                        "Verify {part}: ? no stored answer in \"{}\"",
+                        get_path_for_answers(day)
+                    );
+                }
+            }
+        }
+    }
+
+    println!("\n{ANSI_BOLD}Verified:{ANSI_RESET} {matched} matching, {failed} failing");
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,347 @@
+/// Example manifests: the example inputs of a day with their expected answers.
+/// These drive both the example tests generated by `solution!` and `cargo solve <day> --examples`.
+use std::convert::TryFrom;
+use std::{collections::HashMap, env, fs, path::Path, process, str::FromStr};
+use tinyjson::JsonValue;
+
+use crate::template::params::{Params, with_params};
+use crate::template::{ANSI_BOLD, ANSI_RESET, Day};
+
+#[must_use]
+pub fn get_path_for_manifest(day: Day) -> String {
+    format!("./data/examples/{day}.json")
+}
+
+/// A single example: an input file in `data/examples` and the answers expected for it.
+/// A part without an expected answer is not checked against this example.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Example {
+    pub file: String,
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+    /// Constants that differ between the example and the real input, e.g. a grid size.
+    pub params: Params,
+}
+
+impl Example {
+    pub fn expected(&self, part: u8) -> Option<&str> {
+        match part {
+            1 => self.part_1.as_deref(),
+            2 => self.part_2.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn read_input(&self) -> Result<String, String> {
+        let path = Path::new("data").join("examples").join(&self.file);
+        fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// The examples of a day, stored in `data/examples/{day}.json`:
+///
+/// ```json
+/// {
+///   "examples": [
+///     { "file": "08.txt", "part_1": "40", "part_2": "25272", "params": { "boxes": 10 } }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Examples {
+    pub examples: Vec<Example>,
+}
+
+impl Examples {
+    /// Rehydrate the manifest of a day. If not present, returns no examples.
+    pub fn read(day: Day) -> Result<Self, String> {
+        let path = get_path_for_manifest(day);
+
+        if !Path::new(&path).exists() {
+            return Ok(Examples::default());
+        }
+
+        fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(Examples::try_from)
+            .map_err(|e| format!("{path}: {e}"))
+    }
+
+    /// The examples with an expected answer for `part`.
+    pub fn for_part(&self, part: u8) -> impl Iterator<Item = &Example> {
+        self.examples
+            .iter()
+            .filter(move |example| example.expected(part).is_some())
+    }
+}
+
+/// Outcome of running one part of an example.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExampleResult {
+    pub file: String,
+    pub part: u8,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+impl ExampleResult {
+    pub fn is_pass(&self) -> bool {
+        self.actual.as_deref().map(str::trim_end) == Some(self.expected.trim_end())
+    }
+}
+
+/// Runs `solve` on every example of `day` for each of `parts`.
+pub fn check_examples(
+    day: Day,
+    parts: &[u8],
+    solve: impl Fn(&str, u8) -> Option<String>,
+) -> Result<Vec<ExampleResult>, String> {
+    let examples = Examples::read(day)?;
+    let mut results = vec![];
+
+    for example in &examples.examples {
+        let parts = parts
+            .iter()
+            .filter(|&&part| example.expected(part).is_some());
+        let mut input = None;
+
+        for &part in parts {
+            let input = match &input {
+                Some(input) => input,
+                None => input.insert(example.read_input()?),
+            };
+
+            results.push(ExampleResult {
+                file: example.file.clone(),
+                part,
+                expected: example.expected(part).unwrap().to_string(),
+                actual: with_params(&example.params, || solve(input, part)),
+            });
+        }
+    }
+
+    Ok(results)
+}
+
+/// Body of the example tests generated by `solution!`: checks one part against every example.
+/// Fails if no example has an expected answer for the part, so that a missing manifest or an answer left
+/// at `null` cannot pass unnoticed.
+pub fn assert_examples(day: Day, part: u8, solve: impl Fn(&str, u8) -> Option<String>) {
+    let results = check_examples(day, &[part], solve).unwrap();
+
+    assert!(
+        !results.is_empty(),
+        "No examples with an expected answer for part {part} in \"{}\".",
+        get_path_for_manifest(day)
+    );
+
+    let failures: Vec<String> = results
+        .iter()
+        .filter(|result| !result.is_pass())
+        .map(|result| {
+            format!(
+                "{} part {}: expected {:?}, got {:?}",
+                result.file, result.part, result.expected, result.actual
+            )
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+/// Runs every example of `day` and prints pass/fail. Used by `cargo solve <day> --examples`.
+/// Returns whether all examples passed.
+pub fn run_examples(day: Day, parts: &[u8], solve: impl Fn(&str, u8) -> Option<String>) -> bool {
+    let results = match check_examples(day, parts, solve) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("Could not run examples: {e}");
+            return false;
+        }
+    };
+
+    if results.is_empty() {
+        println!(
+            "No examples with expected answers in \"{}\".",
+            get_path_for_manifest(day)
+        );
+        return true;
+    }
+
+    let mut file = "";
+    for result in &results {
+        if result.file != file {
+            file = &result.file;
+            println!("{ANSI_BOLD}Example {file}{ANSI_RESET}");
+        }
+
+        let actual = result.actual.as_deref().unwrap_or("✖");
+        if result.is_pass() {
+            println!("Part {}: ✔ {actual}", result.part);
+        } else {
+            println!(
+                "Part {}: ✖ expected {}, got {actual}",
+                result.part, result.expected
+            );
+        }
+    }
+
+    let passed = results.iter().filter(|result| result.is_pass()).count();
+    println!(
+        "\n{ANSI_BOLD}Examples:{ANSI_RESET} {passed} passed, {} failed",
+        results.len() - passed
+    );
+
+    passed == results.len()
+}
+
+/// Called by `solution!` first thing in `main`: with `--examples`, runs the examples instead of the real input
+/// and exits with a non-zero status if any failed.
+pub fn run_examples_if_requested(
+    day: Day,
+    parts: &[u8],
+    solve: impl Fn(&str, u8) -> Option<String>,
+) {
+    if !env::args().any(|x| x == "--examples") {
+        return;
+    }
+
+    let passed = run_examples(day, parts, solve);
+    process::exit(if passed { 0 } else { 1 });
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl TryFrom<String> for Examples {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let json = JsonValue::from_str(&value).or(Err("not valid JSON file."))?;
+
+        let json_examples = json
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("expected JSON document to be an object.")?
+            .get("examples")
+            .ok_or("expected JSON document to have key `examples`.")?
+            .get::<Vec<JsonValue>>()
+            .ok_or("expected `json.examples` to be an array.")?;
+
+        Ok(Examples {
+            examples: json_examples
+                .iter()
+                .map(Example::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<&JsonValue> for Example {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected example to be a JSON object.")?;
+
+        let file = json
+            .get("file")
+            .and_then(|v| v.get::<String>())
+            .ok_or("Expected example.file to be a string.")?;
+
+        let answer = |key: &str| match json.get(key) {
+            None => Ok(None),
+            Some(v) if v.is_null() => Ok(None),
+            Some(v) => v
+                .get::<String>()
+                .cloned()
+                .map(Some)
+                .ok_or(format!("Expected example.{key} to be null or string.")),
+        };
+
+        let params: Params = match json.get("params") {
+            None => Params::new(),
+            Some(v) => v
+                .get::<HashMap<String, JsonValue>>()
+                .ok_or("Expected example.params to be an object.")?
+                .iter()
+                .map(|(key, v)| {
+                    let value = match v {
+                        JsonValue::String(s) => s.clone(),
+                        JsonValue::Number(n) => n.to_string(),
+                        JsonValue::Boolean(b) => b.to_string(),
+                        _ => {
+                            return Err(format!(
+                                "Expected example.params.{key} to be a string, number or boolean."
+                            ));
+                        }
+                    };
+                    Ok((key.clone(), value))
+                })
+                .collect::<Result<_, String>>()?,
+        };
+
+        Ok(Example {
+            file: file.clone(),
+            part_1: answer("part_1")?,
+            part_2: answer("part_2")?,
+            params,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{ExampleResult, Examples, assert_examples};
+    use crate::day;
+
+    #[test]
+    fn parses_manifest() {
+        let json = r##"{ "examples": [
+            { "file": "08.txt", "part_1": "40", "part_2": "25272", "params": { "boxes": 10, "mode": "fast" } },
+            { "file": "08-2.txt", "part_2": "#.\n.#" }
+        ] }"##;
+        let examples = Examples::try_from(json.to_string()).unwrap();
+        assert_eq!(examples.examples.len(), 2);
+
+        let first = &examples.examples[0];
+        assert_eq!(first.file, "08.txt");
+        assert_eq!(first.expected(1), Some("40"));
+        assert_eq!(first.params.get("boxes"), Some("10"));
+        assert_eq!(first.params.get("mode"), Some("fast"));
+        assert_eq!(examples.examples[1].params.is_empty(), true);
+
+        assert_eq!(examples.for_part(1).count(), 1);
+        assert_eq!(examples.for_part(2).count(), 2);
+        assert_eq!(examples.examples[1].expected(2), Some("#.\n.#"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_numeric_answers() {
+        Examples::try_from(r#"{ "examples": [{ "file": "01.txt", "part_1": 3 }] }"#.to_string())
+            .unwrap();
+    }
+
+    #[test]
+    fn compares_results() {
+        let result = |actual: Option<&str>| ExampleResult {
+            file: "01.txt".into(),
+            part: 1,
+            expected: "3".into(),
+            actual: actual.map(Into::into),
+        };
+        assert!(result(Some("3")).is_pass());
+        assert!(result(Some("3\n")).is_pass());
+        assert!(!result(Some("4")).is_pass());
+        assert!(!result(None).is_pass());
+    }
+
+    #[test]
+    #[should_panic(expected = "No examples with an expected answer for part 1")]
+    fn fails_without_expected_answers() {
+        // There is no manifest for day 25.
+        assert_examples(day!(25), 1, |_, _| None);
+    }
+}
@@ -0,0 +1,483 @@
+/// Benchmark history: every stored `cargo time` run of a day is kept (up to `HISTORY_LENGTH` runs)
+/// together with when, at which commit and on which machine it ran, so that later runs can be compared with it.
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, fs, thread};
+
+use tinyjson::JsonValue;
+
+use crate::template::limits::Limit;
+use crate::template::runner::Stage;
+use crate::template::timings::{Timing, Timings};
+use crate::template::{ANSI_BOLD, ANSI_GREEN, ANSI_RED, ANSI_RESET, Day};
+
+/// Number of runs kept per day.
+pub const HISTORY_LENGTH: usize = 20;
+
+static GIT_DIR_PATH: &str = "./.git";
+
+const STAGES: [Stage; 3] = [Stage::Parse, Stage::Part(1), Stage::Part(2)];
+
+/// When, at which commit and on which machine a benchmark ran.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Provenance {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The checked-out commit, if the repository is a git checkout.
+    pub commit: Option<String>,
+    /// Fingerprint of the machine, see `host_fingerprint`.
+    pub host: String,
+}
+
+impl Provenance {
+    pub fn current() -> Self {
+        Provenance {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            commit: resolve_ref(Path::new(GIT_DIR_PATH), "HEAD"),
+            host: host_fingerprint(),
+        }
+    }
+}
+
+/// A single run of a day: the median of each stage that finished.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub commit: Option<String>,
+    pub host: String,
+    pub parse: Option<Duration>,
+    pub part_1: Option<Duration>,
+    pub part_2: Option<Duration>,
+    pub limit: Option<Limit>,
+}
+
+impl HistoryEntry {
+    pub fn new(timing: &Timing, provenance: &Provenance) -> Self {
+        HistoryEntry {
+            timestamp: provenance.timestamp,
+            commit: provenance.commit.clone(),
+            host: provenance.host.clone(),
+            parse: timing.parse_stats.map(|stats| stats.median),
+            part_1: timing.part_1_stats.map(|stats| stats.median),
+            part_2: timing.part_2_stats.map(|stats| stats.median),
+            limit: timing.limit,
+        }
+    }
+
+    pub fn get(&self, stage: Stage) -> Option<Duration> {
+        match stage {
+            Stage::Parse => self.parse,
+            Stage::Part(1) => self.part_1,
+            Stage::Part(2) => self.part_2,
+            Stage::Part(_) => None,
+        }
+    }
+
+    /// Whether this run was on `host` and, if given, at a commit starting with `commit`.
+    fn matches(&self, host: &str, commit: Option<&str>) -> bool {
+        self.host == host
+            && commit.is_none_or(|commit| {
+                self.commit
+                    .as_deref()
+                    .is_some_and(|c| c.starts_with(commit))
+            })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Resolves `HEAD`, a branch, a tag or a remote branch to a commit hash by reading the git directory,
+/// so that git does not need to be installed.
+pub fn resolve_ref(git_dir: &Path, name: &str) -> Option<String> {
+    let candidates = if name == "HEAD" || name.starts_with("refs/") {
+        vec![name.to_string()]
+    } else {
+        vec![
+            format!("refs/heads/{name}"),
+            format!("refs/tags/{name}"),
+            format!("refs/remotes/{name}"),
+        ]
+    };
+
+    candidates
+        .iter()
+        .find_map(|candidate| read_ref(git_dir, candidate, 0))
+}
+
+fn read_ref(git_dir: &Path, name: &str, depth: usize) -> Option<String> {
+    // symbolic refs point to other refs, give up on cycles.
+    if depth > 5 {
+        return None;
+    }
+
+    if let Ok(content) = fs::read_to_string(git_dir.join(name)) {
+        let content = content.trim();
+        return match content.strip_prefix("ref: ") {
+            Some(target) => read_ref(git_dir, target, depth + 1),
+            None => Some(content.to_string()),
+        };
+    }
+
+    // refs that are not loose files are listed in `packed-refs` as `<hash> <name>`,
+    // with annotated tags followed by `^<hash of the commit>`.
+    let packed = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+    let mut lines = packed.lines().skip_while(|line| {
+        line.split_once(' ')
+            .is_none_or(|(_, ref_name)| ref_name != name)
+    });
+
+    let hash = lines.next()?.split_once(' ')?.0;
+    match lines.next().and_then(|line| line.strip_prefix('^')) {
+        Some(peeled) => Some(peeled.to_string()),
+        None => Some(hash.to_string()),
+    }
+}
+
+/// A short, stable hash of the host name, OS, architecture and CPU.
+/// Timings are only comparable between runs on the same machine; the hash keeps the host name out of `timings.json`.
+pub fn host_fingerprint() -> String {
+    let hostname = fs::read_to_string("/etc/hostname")
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .unwrap_or_default();
+
+    let cpu = fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find(|line| line.starts_with("model name"))
+                .and_then(|line| line.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        })
+        .unwrap_or_default();
+
+    let threads = thread::available_parallelism().map_or(0, |n| n.get());
+
+    let description = format!(
+        "{}|{}|{}|{threads}|{cpu}",
+        hostname.trim(),
+        env::consts::OS,
+        env::consts::ARCH
+    );
+
+    format!("{:016x}", fnv1a(description.as_bytes()))
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output is the same across Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A stage timed both in a stored run and in the current one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub day: Day,
+    pub stage: Stage,
+    pub before: Duration,
+    pub after: Duration,
+}
+
+impl Change {
+    /// Relative change of the median, `0.1` being 10% slower.
+    pub fn ratio(&self) -> f64 {
+        self.after.as_secs_f64() / self.before.as_secs_f64() - 1.0
+    }
+
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.ratio() > threshold
+    }
+
+    pub fn is_improvement(&self, threshold: f64) -> bool {
+        self.ratio() < -threshold
+    }
+}
+
+/// Pairs the stages of each day in `new` with the latest stored run of that day on `host`,
+/// made at a commit starting with `commit` if given. Days without such a run are returned separately.
+pub fn compare(
+    stored: &Timings,
+    new: &Timings,
+    host: &str,
+    commit: Option<&str>,
+) -> (Vec<Change>, Vec<Day>) {
+    let mut changes = vec![];
+    let mut missing = vec![];
+
+    for timing in &new.data {
+        let baseline = stored
+            .data
+            .iter()
+            .find(|t| t.day == timing.day)
+            .and_then(|t| t.history.iter().rev().find(|e| e.matches(host, commit)));
+
+        let Some(baseline) = baseline else {
+            missing.push(timing.day);
+            continue;
+        };
+
+        let Some(current) = timing.history.last() else {
+            continue;
+        };
+
+        for stage in STAGES {
+            if let (Some(before), Some(after)) = (baseline.get(stage), current.get(stage)) {
+                changes.push(Change {
+                    day: timing.day,
+                    stage,
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+
+    (changes, missing)
+}
+
+/// Prints a table of `changes`, marking regressions red and improvements green.
+/// Returns whether any stage regressed by more than `threshold`.
+pub fn print_comparison(
+    changes: &[Change],
+    missing: &[Day],
+    against: &str,
+    threshold: f64,
+) -> bool {
+    println!("\n{ANSI_BOLD}Compared with {against}:{ANSI_RESET}\n");
+
+    if !changes.is_empty() {
+        println!(
+            "{:<6} {:<8} {:>12} {:>12} {:>9}",
+            "Day", "Stage", "Before", "After", "Change"
+        );
+    }
+
+    for change in changes {
+        let color = if change.is_regression(threshold) {
+            ANSI_RED
+        } else if change.is_improvement(threshold) {
+            ANSI_GREEN
+        } else {
+            ""
+        };
+
+        println!(
+            "{color}{:<6} {:<8} {:>12} {:>12} {:>+8.1}%{ANSI_RESET}",
+            change.day.to_string(),
+            change.stage.to_string(),
+            format!("{:.1?}", change.before),
+            format!("{:.1?}", change.after),
+            change.ratio() * 100.0
+        );
+    }
+
+    for day in missing {
+        println!("Day {day}: no stored run to compare with.");
+    }
+
+    let regressions = changes
+        .iter()
+        .filter(|change| change.is_regression(threshold))
+        .count();
+
+    println!(
+        "\n{ANSI_BOLD}Regressions:{ANSI_RESET} {regressions} stage(s) slower by more than {:.0}%",
+        threshold * 100.0
+    );
+
+    regressions > 0
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<&HistoryEntry> for JsonValue {
+    fn from(value: &HistoryEntry) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        let nanos = |duration: Option<Duration>| {
+            duration.map_or(JsonValue::Null, |d| JsonValue::Number(d.as_nanos() as f64))
+        };
+
+        map.insert(
+            "timestamp".into(),
+            JsonValue::Number(value.timestamp as f64),
+        );
+        map.insert(
+            "commit".into(),
+            value
+                .commit
+                .clone()
+                .map_or(JsonValue::Null, JsonValue::String),
+        );
+        map.insert("host".into(), JsonValue::String(value.host.clone()));
+        map.insert("parse_nanos".into(), nanos(value.parse));
+        map.insert("part_1_nanos".into(), nanos(value.part_1));
+        map.insert("part_2_nanos".into(), nanos(value.part_2));
+        map.insert(
+            "limit".into(),
+            value.limit.map_or(JsonValue::Null, JsonValue::from),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for HistoryEntry {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected history entry to be a JSON object.")?;
+
+        let timestamp = json
+            .get("timestamp")
+            .and_then(|v| v.get::<f64>())
+            .ok_or("Expected history.timestamp to be a number.")?;
+
+        let host = json
+            .get("host")
+            .and_then(|v| v.get::<String>())
+            .ok_or("Expected history.host to be a string.")?;
+
+        let commit = match json.get("commit") {
+            Some(v) if !v.is_null() => Some(
+                v.get::<String>()
+                    .ok_or("Expected history.commit to be null or string.")?
+                    .clone(),
+            ),
+            _ => None,
+        };
+
+        let nanos = |key: &str| match json.get(key) {
+            Some(v) if !v.is_null() => v
+                .get::<f64>()
+                .map(|nanos| Some(Duration::from_nanos(*nanos as u64)))
+                .ok_or(format!("Expected history.{key} to be null or a number.")),
+            _ => Ok(None),
+        };
+
+        let limit = match json.get("limit") {
+            Some(v) if !v.is_null() => Some(Limit::try_from(v)?),
+            _ => None,
+        };
+
+        Ok(HistoryEntry {
+            timestamp: *timestamp as u64,
+            commit,
+            host: host.clone(),
+            parse: nanos("parse_nanos")?,
+            part_1: nanos("part_1_nanos")?,
+            part_2: nanos("part_2_nanos")?,
+            limit,
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Change, HistoryEntry, compare, resolve_ref};
+    use crate::day;
+    use crate::template::runner::Stage;
+    use crate::template::timings::{Timing, Timings};
+    use std::{env, fs, time::Duration};
+    use tinyjson::JsonValue;
+
+    fn entry(host: &str, commit: &str, part_1_millis: u64) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: 1_700_000_000,
+            commit: Some(commit.into()),
+            host: host.into(),
+            parse: None,
+            part_1: Some(Duration::from_millis(part_1_millis)),
+            part_2: None,
+            limit: None,
+        }
+    }
+
+    fn timings(history: Vec<HistoryEntry>) -> Timings {
+        Timings {
+            data: vec![Timing {
+                day: day!(8),
+                parse: None,
+                part_1: Some("10ms".into()),
+                part_2: None,
+                parse_stats: None,
+                part_1_stats: None,
+                part_2_stats: None,
+                total_nanos: 1e+7,
+                limit: None,
+                history,
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trips_entries() {
+        let entry = entry("0123456789abcdef", "3f2a1b7c", 12);
+        let json = JsonValue::from(&entry);
+        assert_eq!(HistoryEntry::try_from(&json).unwrap(), entry);
+    }
+
+    #[test]
+    fn compares_with_latest_run_on_host() {
+        let stored = timings(vec![
+            entry("a", "1111", 10),
+            entry("b", "2222", 5),
+            entry("a", "3333", 20),
+        ]);
+        let new = timings(vec![entry("a", "4444", 23)]);
+
+        let (changes, missing) = compare(&stored, &new, "a", None);
+        assert!(missing.is_empty());
+        assert_eq!(
+            changes,
+            vec![Change {
+                day: day!(8),
+                stage: Stage::Part(1),
+                before: Duration::from_millis(20),
+                after: Duration::from_millis(23),
+            }]
+        );
+        assert!(changes[0].is_regression(0.1));
+        assert!(!changes[0].is_regression(0.2));
+
+        let (changes, _) = compare(&stored, &new, "a", Some("11"));
+        assert_eq!(changes[0].before, Duration::from_millis(10));
+        assert!(changes[0].is_regression(0.1));
+
+        let (changes, missing) = compare(&stored, &new, "c", None);
+        assert!(changes.is_empty());
+        assert_eq!(missing, vec![day!(8)]);
+    }
+
+    #[test]
+    fn resolves_git_refs() {
+        let git_dir = env::temp_dir().join(format!("aoc-history-test-{}", std::process::id()));
+        fs::create_dir_all(git_dir.join("refs/heads")).unwrap();
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git_dir.join("refs/heads/main"), "aaaa\n").unwrap();
+        fs::write(
+            git_dir.join("packed-refs"),
+            "# pack-refs with: peeled\nbbbb refs/heads/old\ncccc refs/tags/v1\n^dddd\n",
+        )
+        .unwrap();
+
+        assert_eq!(resolve_ref(&git_dir, "HEAD"), Some("aaaa".into()));
+        assert_eq!(resolve_ref(&git_dir, "main"), Some("aaaa".into()));
+        assert_eq!(resolve_ref(&git_dir, "old"), Some("bbbb".into()));
+        assert_eq!(resolve_ref(&git_dir, "v1"), Some("dddd".into()));
+        assert_eq!(resolve_ref(&git_dir, "missing"), None);
+
+        fs::remove_dir_all(git_dir).unwrap();
+    }
+}
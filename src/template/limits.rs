@@ -0,0 +1,287 @@
+/// Per-day resource limits, so that a runaway solution cannot hang a run of all days or exhaust the machine.
+///
+/// A solution binary enforces its own limits. A watchdog thread ends the process after `--timeout <seconds>`,
+/// and `LimitedAllocator` refuses the first allocation that would hold more than `--max-memory <MiB>` on the heap.
+/// The process then exits with `TIMEOUT_EXIT_CODE` or `OOM_EXIT_CODE`, which `run_multi` reports as `TIMEOUT` or `OOM`.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::time::Duration;
+use std::{env, process, thread};
+
+use tinyjson::JsonValue;
+
+use crate::template::{ANSI_BOLD, ANSI_RESET};
+
+/// Exit code of a solution stopped by `--timeout`, the same as GNU `timeout`.
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+/// Exit code of a solution stopped by `--max-memory`, the same as a process killed by the OOM killer.
+pub const OOM_EXIT_CODE: i32 = 137;
+
+/// The limit that stopped a day before it finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Timeout,
+    Memory,
+}
+
+impl Limit {
+    pub fn from_exit_code(code: Option<i32>) -> Option<Self> {
+        match code {
+            Some(TIMEOUT_EXIT_CODE) => Some(Limit::Timeout),
+            Some(OOM_EXIT_CODE) => Some(Limit::Memory),
+            _ => None,
+        }
+    }
+
+    /// Name of the limit in `timings.json`.
+    fn key(self) -> &'static str {
+        match self {
+            Limit::Timeout => "timeout",
+            Limit::Memory => "oom",
+        }
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Timeout => write!(f, "TIMEOUT"),
+            Limit::Memory => write!(f, "OOM"),
+        }
+    }
+}
+
+impl From<Limit> for JsonValue {
+    fn from(value: Limit) -> Self {
+        JsonValue::String(value.key().into())
+    }
+}
+
+impl TryFrom<&JsonValue> for Limit {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        match value.get::<String>().map(String::as_str) {
+            Some("timeout") => Ok(Limit::Timeout),
+            Some("oom") => Ok(Limit::Memory),
+            _ => Err("Expected limit to be \"timeout\" or \"oom\".".into()),
+        }
+    }
+}
+
+/// Limits of a single day run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// Wall-clock time for the whole day, including benching.
+    pub timeout: Option<Duration>,
+    /// Heap memory in MiB.
+    pub max_memory: Option<u64>,
+}
+
+impl Limits {
+    pub fn new(timeout_secs: Option<f64>, max_memory: Option<u64>) -> Self {
+        Limits {
+            timeout: timeout_secs.map(Duration::from_secs_f64),
+            max_memory,
+        }
+    }
+
+    /// Reads `--timeout <seconds>` and `--max-memory <MiB>` from the command line.
+    pub fn from_args() -> Self {
+        let args: Vec<String> = env::args().collect();
+
+        let value_of = |name: &str| {
+            args.iter()
+                .position(|x| x == name)
+                .and_then(|i| args.get(i + 1))
+        };
+
+        Limits::new(
+            value_of("--timeout").and_then(|secs| secs.parse().ok()),
+            value_of("--max-memory").and_then(|mib| mib.parse().ok()),
+        )
+    }
+
+    /// The arguments that pass these limits on to a solution binary.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        if let Some(timeout) = self.timeout {
+            args.push("--timeout".into());
+            args.push(timeout.as_secs_f64().to_string());
+        }
+
+        if let Some(max_memory) = self.max_memory {
+            args.push("--max-memory".into());
+            args.push(max_memory.to_string());
+        }
+
+        args
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timeout.is_none() && self.max_memory.is_none()
+    }
+
+    /// Ends the process once a limit is exceeded. Called by `solution!` at the start of `main`.
+    pub fn enforce(&self) {
+        if self.is_empty() {
+            return;
+        }
+
+        if let Some(max_memory) = self.max_memory {
+            let bytes = max_memory.saturating_mul(1024 * 1024);
+            MEMORY_LIMIT.store(
+                usize::try_from(bytes).unwrap_or(usize::MAX),
+                Ordering::Relaxed,
+            );
+            if !LimitedAllocator::is_counting() {
+                eprintln!(
+                    "--max-memory is only enforced with `LimitedAllocator` as the global allocator, e.g. not with dhat."
+                );
+            }
+        }
+
+        if let Some(timeout) = self.timeout {
+            thread::spawn(move || {
+                thread::sleep(timeout);
+                eprintln!(
+                    "\n{ANSI_BOLD}{}{ANSI_RESET}: exceeded {timeout:.1?}.",
+                    Limit::Timeout
+                );
+                process::exit(TIMEOUT_EXIT_CODE);
+            });
+        }
+    }
+}
+
+unsafe extern "C" {
+    /// Ends the process without running exit handlers or destructors, which may allocate.
+    fn _exit(status: i32) -> !;
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Heap limit in bytes, `usize::MAX` while no limit is set.
+static MEMORY_LIMIT: AtomicUsize = AtomicUsize::new(usize::MAX);
+/// Bytes allocated since the limit was set. Memory allocated before and freed after can make this negative.
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+
+/// The system allocator, counting allocated bytes while a memory limit is set.
+/// Without a limit it only adds a relaxed load per call, so benchmarks are not affected.
+///
+/// `solution!` makes it the global allocator of each day binary, unless profiling with dhat.
+/// It is not installed by the library itself, so other binaries, tests and benches keep their own allocator.
+pub struct LimitedAllocator;
+
+impl LimitedAllocator {
+    /// Whether allocations are counted, i.e. a limit is set and this is the global allocator.
+    fn is_counting() -> bool {
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        let probe = std::hint::black_box(Box::new(0u64));
+        let counted = ALLOCATED.load(Ordering::Relaxed) != before;
+        drop(probe);
+        counted
+    }
+
+    fn grow(size: usize) {
+        let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
+        if limit == usize::MAX {
+            return;
+        }
+
+        let allocated = ALLOCATED.fetch_add(size as isize, Ordering::Relaxed) + size as isize;
+        if allocated > 0 && allocated as usize > limit {
+            Self::refuse(limit);
+        }
+    }
+
+    /// Ends the process instead of making an allocation over the limit.
+    /// Neither the message nor `_exit` allocate, so this is safe to call from within the allocator.
+    fn refuse(limit: usize) -> ! {
+        MEMORY_LIMIT.store(usize::MAX, Ordering::Relaxed);
+        let max_memory = limit / (1024 * 1024);
+        let _ = writeln!(
+            io::stderr(),
+            "\n{ANSI_BOLD}{}{ANSI_RESET}: exceeded {max_memory} MiB.",
+            Limit::Memory
+        );
+        unsafe { _exit(OOM_EXIT_CODE) }
+    }
+
+    fn shrink(size: usize) {
+        if MEMORY_LIMIT.load(Ordering::Relaxed) != usize::MAX {
+            ALLOCATED.fetch_sub(size as isize, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for LimitedAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::grow(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::grow(layout.size());
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::shrink(layout.size());
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            Self::grow(new_size - layout.size());
+        } else {
+            Self::shrink(layout.size() - new_size);
+        }
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Limit, Limits, OOM_EXIT_CODE, TIMEOUT_EXIT_CODE};
+    use std::time::Duration;
+    use tinyjson::JsonValue;
+
+    #[test]
+    fn passes_limits_as_args() {
+        let limits = Limits::new(Some(2.5), Some(512));
+        assert_eq!(limits.timeout, Some(Duration::from_millis(2500)));
+        assert_eq!(
+            limits.to_args(),
+            vec!["--timeout", "2.5", "--max-memory", "512"]
+        );
+        assert!(Limits::default().to_args().is_empty());
+    }
+
+    #[test]
+    fn reads_exit_codes() {
+        assert_eq!(
+            Limit::from_exit_code(Some(TIMEOUT_EXIT_CODE)),
+            Some(Limit::Timeout)
+        );
+        assert_eq!(
+            Limit::from_exit_code(Some(OOM_EXIT_CODE)),
+            Some(Limit::Memory)
+        );
+        assert_eq!(Limit::from_exit_code(Some(0)), None);
+        assert_eq!(Limit::from_exit_code(None), None);
+    }
+
+    #[test]
+    fn round_trips_limits() {
+        for limit in [Limit::Timeout, Limit::Memory] {
+            assert_eq!(Limit::try_from(&JsonValue::from(limit)), Ok(limit));
+        }
+        assert!(Limit::try_from(&JsonValue::String("slow".into())).is_err());
+    }
+}
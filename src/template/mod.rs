@@ -3,9 +3,14 @@ use std::{env, fs};
 
 pub mod aoc_cli;
 pub mod commands;
+pub mod examples;
+pub mod limits;
+pub mod params;
 pub mod runner;
 
+mod answers;
 mod day;
+mod history;
 mod readme_benchmarks;
 mod run_multi;
 mod timings;
@@ -15,6 +20,8 @@ pub use self::day::*;
 pub const ANSI_ITALIC: &str = "\x1b[3m";
 pub const ANSI_BOLD: &str = "\x1b[1m";
 pub const ANSI_RESET: &str = "\x1b[0m";
+pub const ANSI_RED: &str = "\x1b[31m";
+pub const ANSI_GREEN: &str = "\x1b[32m";
 
 /// Helper function that reads a text file to a string.
 #[must_use]
@@ -40,6 +47,13 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+///
+/// Passing `parse` (e.g. `solution!(5, parse)` or `solution!(5, parse, 1)`) selects the two-stage form:
+/// the input is handed to `parse(&str) -> P` once, and each part takes the parsed `&P`.
+/// Parsing is then timed separately from the parts.
+///
+/// Each part also gets a test that checks it against the examples listed in `data/examples/{day}.json`,
+/// and running the binary with `--examples` runs them all and reports pass/fail.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
@@ -51,19 +65,105 @@ macro_rules! solution {
     ($day:expr, 2) => {
         $crate::solution!(@impl $day, [part_two, 2]);
     };
+    ($day:expr, parse) => {
+        $crate::solution!(@impl_parse $day, parse, [part_one, 1] [part_two, 2]);
+    };
+    ($day:expr, parse, 1) => {
+        $crate::solution!(@impl_parse $day, parse, [part_one, 1]);
+    };
+    ($day:expr, parse, 2) => {
+        $crate::solution!(@impl_parse $day, parse, [part_two, 2]);
+    };
+
+    (@impl $day:expr, $( [$func:ident, $part:literal] )*) => {
+        $crate::solution!(@common $day, $( [$func, $part] )*);
+
+        /// Solves a part for an example input, for `--examples` and the generated example tests.
+        fn solve_example(input: &str, part: u8) -> Option<String> {
+            match part {
+                $( $part => $func(input).map(|answer| answer.to_string()), )*
+                _ => None,
+            }
+        }
+
+        /// Runs every part on the real input. Also the entry point of the day in the multi-day registry.
+        pub fn run_day(
+            options: &$crate::template::runner::RunOptions,
+        ) -> Vec<$crate::template::runner::StageRecord> {
+            use $crate::template::runner::*;
+            let input = $crate::template::read_file("inputs", DAY);
+            vec![$( solve_part(options, $func, &input, $part) ),*]
+        }
+
+        $crate::solution!(@main);
+    };
+
+    (@impl_parse $day:expr, $parse:expr, $( [$func:ident, $part:literal] )*) => {
+        $crate::solution!(@common $day, $( [$func, $part] )*);
+
+        /// Solves a part for an example input, for `--examples` and the generated example tests.
+        fn solve_example(input: &str, part: u8) -> Option<String> {
+            let parsed = $parse(input);
+            match part {
+                $( $part => $func(&parsed).map(|answer| answer.to_string()), )*
+                _ => None,
+            }
+        }
+
+        /// Runs every part on the real input. Also the entry point of the day in the multi-day registry.
+        pub fn run_day(
+            options: &$crate::template::runner::RunOptions,
+        ) -> Vec<$crate::template::runner::StageRecord> {
+            use $crate::template::runner::*;
+            let input = $crate::template::read_file("inputs", DAY);
+            let (parsed, parse_record) = solve_parse(options, $parse, &input);
+            let mut records = vec![parse_record];
+            $( records.push(solve_part(options, $func, &parsed, $part)); )*
+            records
+        }
+
+        $crate::solution!(@main);
+    };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@main) => {
+        fn main() {
+            use $crate::template::runner::*;
+            $crate::template::params::init_from_args();
+            $crate::template::limits::Limits::from_args().enforce();
+            $crate::template::examples::run_examples_if_requested(DAY, PARTS, solve_example);
+            run_day(&RunOptions {
+                submit: Some(DAY),
+                ..RunOptions::from_args()
+            });
+        }
+    };
+
+    (@common $day:expr, $( [$func:ident, $part:literal] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
+        /// The parts this solution runs.
+        const PARTS: &[u8] = &[$( $part ),*];
+
         #[cfg(feature = "dhat-heap")]
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
-        fn main() {
-            use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+        // counts the heap for `--max-memory`. Days linked into the main binary run without limits.
+        #[cfg(not(any(test, feature = "dhat-heap", feature = "registry")))]
+        #[global_allocator]
+        static ALLOC: $crate::template::limits::LimitedAllocator =
+            $crate::template::limits::LimitedAllocator;
+
+        /// One test per part, checking it against the examples in `data/examples/{day}.json`.
+        #[cfg(test)]
+        mod example_tests {
+            $(
+                #[test]
+                fn $func() {
+                    $crate::template::examples::assert_examples(super::DAY, $part, super::solve_example);
+                }
+            )*
         }
     };
 }
@@ -0,0 +1,193 @@
+/// Named puzzle parameters: constants that differ between the examples and the real input,
+/// such as the number of steps to simulate or the size of a grid.
+///
+/// Solutions read them with defaults for the real input, e.g. `param("boxes", 1000)`.
+/// Values are set with `--param boxes=10` on the command line, or by the `params` of an example
+/// in `data/examples/{day}.json` when running examples (both `--examples` and the generated tests).
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::{env, process};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params(BTreeMap<String, String>);
+
+impl Params {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The value of `name` parsed as a `T`, or `default` if it is not set.
+    /// Panics if the value is set but does not parse, as the run would be meaningless.
+    pub fn get_or<T: FromStr>(&self, name: &str, default: T) -> T
+    where
+        T::Err: Debug,
+    {
+        parse_or(name, self.get(name), default)
+    }
+
+    /// Collects every `--param name=value` (or `--param=name=value`) from the arguments.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut params = Params::new();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let assignment = if arg == "--param" {
+                args.next().ok_or("Expected name=value after --param.")?
+            } else if let Some(assignment) = arg.strip_prefix("--param=") {
+                assignment.to_string()
+            } else {
+                continue;
+            };
+
+            let (name, value) = assignment
+                .split_once('=')
+                .ok_or(format!("Expected --param name=value, got {assignment:?}."))?;
+            params.insert(name.trim(), value.trim());
+        }
+
+        Ok(params)
+    }
+}
+
+impl FromIterator<(String, String)> for Params {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(iter: I) -> Self {
+        Params(iter.into_iter().collect())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Params of the whole run, from the command line. Visible to every thread (e.g. rayon workers).
+static RUN_PARAMS: OnceLock<Params> = OnceLock::new();
+
+thread_local! {
+    /// Params of the example being solved on this thread. Kept per thread so that tests can run in parallel.
+    static SCOPED_PARAMS: RefCell<Option<Params>> = const { RefCell::new(None) };
+}
+
+/// Reads `--param` arguments into the params of the run. Called by `solution!` at the start of `main`.
+pub fn init_from_args() {
+    match Params::from_args(env::args().skip(1)) {
+        Ok(params) => {
+            let _ = RUN_PARAMS.set(params);
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Runs `f` with `params` taking precedence over the params of the run, on the current thread only.
+pub fn with_params<R>(params: &Params, f: impl FnOnce() -> R) -> R {
+    let previous = SCOPED_PARAMS.with(|scoped| scoped.replace(Some(params.clone())));
+    let result = f();
+    SCOPED_PARAMS.with(|scoped| scoped.replace(previous));
+    result
+}
+
+/// The value of a puzzle parameter, or `default` if it is not set for this run.
+///
+/// Example params are only visible on the thread that runs the solution, so read params
+/// before handing work to other threads.
+pub fn param<T: FromStr>(name: &str, default: T) -> T
+where
+    T::Err: Debug,
+{
+    let value = SCOPED_PARAMS
+        .with(|scoped| {
+            scoped
+                .borrow()
+                .as_ref()
+                .and_then(|params| params.get(name).map(String::from))
+        })
+        .or_else(|| RUN_PARAMS.get()?.get(name).map(String::from));
+
+    parse_or(name, value.as_deref(), default)
+}
+
+fn parse_or<T: FromStr>(name: &str, value: Option<&str>, default: T) -> T
+where
+    T::Err: Debug,
+{
+    match value {
+        Some(value) => value
+            .parse()
+            .unwrap_or_else(|e| panic!("Invalid value {value:?} for param `{name}`: {e:?}")),
+        None => default,
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Params, param, with_params};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_args() {
+        let params = Params::from_args(args(&[
+            "--time",
+            "--param",
+            "boxes=10",
+            "--param=mode=fast",
+        ]))
+        .unwrap();
+        assert_eq!(params.get("boxes"), Some("10"));
+        assert_eq!(params.get("mode"), Some("fast"));
+        assert_eq!(params.get("steps"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_missing_value() {
+        Params::from_args(args(&["--param", "boxes"])).unwrap();
+    }
+
+    #[test]
+    fn reads_with_defaults() {
+        let params = Params::from_args(args(&["--param", "boxes=10"])).unwrap();
+        assert_eq!(params.get_or("boxes", 1000_usize), 10);
+        assert_eq!(params.get_or("steps", 64_usize), 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_for_invalid_value() {
+        let params = Params::from_args(args(&["--param", "boxes=ten"])).unwrap();
+        params.get_or("boxes", 1000_usize);
+    }
+
+    #[test]
+    fn scopes_params_to_closure() {
+        let mut params = Params::new();
+        params.insert("boxes", "10");
+
+        assert_eq!(param("boxes", 1000_usize), 1000);
+        assert_eq!(with_params(&params, || param("boxes", 1000_usize)), 10);
+        assert_eq!(param("boxes", 1000_usize), 1000);
+    }
+}
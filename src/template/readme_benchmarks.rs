@@ -3,6 +3,8 @@
 use std::{fs, io};
 
 use crate::template::Day;
+use crate::template::limits::Limit;
+use crate::template::runner::BenchStats;
 use crate::template::timings::Timings;
 
 static MARKER: &str = "<!--- benchmarking table --->";
@@ -60,18 +62,19 @@ fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String
         MARKER.into(),
         header,
         String::new(),
-        "| Day | Part 1 | Part 2 |".into(),
-        "| :---: | :---: | :---:  |".into(),
+        "| Day | Parse | Part 1 | Part 2 |".into(),
+        "| :---: | :---: | :---: | :---:  |".into(),
     ];
 
     for timing in timings.data {
         let path = get_path_for_bin(timing.day);
         lines.push(format!(
-            "| [Day {}]({}) | `{}` | `{}` |",
+            "| [Day {}]({}) | `{}` | `{}` | `{}` |",
             timing.day.into_inner(),
             path,
-            timing.part_1.unwrap_or_else(|| "-".into()),
-            timing.part_2.unwrap_or_else(|| "-".into())
+            format_cell(timing.parse, timing.parse_stats, None),
+            format_cell(timing.part_1, timing.part_1_stats, timing.limit),
+            format_cell(timing.part_2, timing.part_2_stats, timing.limit)
         ));
     }
 
@@ -82,6 +85,16 @@ fn construct_table(prefix: &str, timings: Timings, total_millis: f64) -> String
     lines.join("\n")
 }
 
+/// Formats a part as `median ± spread`, falling back to the plain time for timings without stats.
+/// Parts that did not finish because the day hit a limit show that limit.
+fn format_cell(time: Option<String>, stats: Option<BenchStats>, limit: Option<Limit>) -> String {
+    match (time, stats) {
+        (Some(_), Some(stats)) => format!("{:.1?} ± {:.1?}", stats.median, stats.stddev),
+        (Some(time), None) => time,
+        (None, _) => limit.map_or("-".into(), |limit| limit.to_string()),
+    }
+}
+
 fn update_content(s: &mut String, timings: Timings, total_millis: f64) -> Result<(), Error> {
     let positions = locate_table(s)?;
     let table = construct_table("##", timings, total_millis);
@@ -101,28 +114,49 @@ pub fn update(timings: Timings) -> Result<(), Error> {
 #[cfg(feature = "test_lib")]
 mod tests {
     use super::{MARKER, update_content};
+    use crate::template::limits::Limit;
+    use crate::template::runner::BenchStats;
     use crate::{day, template::timings::Timing, template::timings::Timings};
+    use std::time::Duration;
 
     fn get_mock_timings() -> Timings {
         Timings {
             data: vec![
                 Timing {
                     day: day!(1),
+                    parse: None,
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 3e+10,
+                    limit: None,
+                    history: vec![],
                 },
                 Timing {
                     day: day!(2),
+                    parse: Some("5ms".into()),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 7e+10,
+                    limit: None,
+                    history: vec![],
                 },
                 Timing {
                     day: day!(4),
+                    parse: None,
                     part_1: Some("40ms".into()),
                     part_2: Some("50ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 9e+10,
+                    limit: None,
+                    history: vec![],
                 },
             ],
         }
@@ -158,6 +192,32 @@ mod tests {
         assert_eq!(s.matches("## Benchmarks").collect::<Vec<&str>>().len(), 1);
     }
 
+    #[test]
+    fn formats_median_and_spread() {
+        let mut timings = get_mock_timings();
+        timings.data[0].part_1_stats = Some(BenchStats {
+            samples: 90,
+            outliers: 10,
+            min: Duration::from_micros(9500),
+            median: Duration::from_millis(10),
+            p95: Duration::from_micros(11200),
+            stddev: Duration::from_micros(350),
+        });
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, timings, 190.0).unwrap();
+        assert!(s.contains("| [Day 1](./src/bin/01.rs) | `-` | `10.0ms ± 350.0µs` | `20ms` |"));
+    }
+
+    #[test]
+    fn formats_stopped_days() {
+        let mut timings = get_mock_timings();
+        timings.data[2].part_2 = None;
+        timings.data[2].limit = Some(Limit::Timeout);
+        let mut s = format!("{}\n{}", MARKER, MARKER);
+        update_content(&mut s, timings, 190.0).unwrap();
+        assert!(s.contains("| [Day 4](./src/bin/04.rs) | `-` | `40ms` | `TIMEOUT` |"));
+    }
+
     #[test]
     fn format_benchmarks() {
         let mut s = format!("foo\nbar\n{}\n{}\nbaz", MARKER, MARKER);
@@ -168,11 +228,11 @@ mod tests {
             "<!--- benchmarking table --->",
             "## Benchmarks",
             "",
-            "| Day | Part 1 | Part 2 |",
-            "| :---: | :---: | :---:  |",
-            "| [Day 1](./src/bin/01.rs) | `10ms` | `20ms` |",
-            "| [Day 2](./src/bin/02.rs) | `30ms` | `40ms` |",
-            "| [Day 4](./src/bin/04.rs) | `40ms` | `50ms` |",
+            "| Day | Parse | Part 1 | Part 2 |",
+            "| :---: | :---: | :---: | :---:  |",
+            "| [Day 1](./src/bin/01.rs) | `-` | `10ms` | `20ms` |",
+            "| [Day 2](./src/bin/02.rs) | `5ms` | `30ms` | `40ms` |",
+            "| [Day 4](./src/bin/04.rs) | `-` | `40ms` | `50ms` |",
             "",
             "**Total: 190.00ms**",
             "<!--- benchmarking table --->",
@@ -1,5 +1,10 @@
-use std::{collections::HashSet, io};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::{io, panic, thread, time::Duration};
 
+use crate::template::limits::{Limit, Limits};
+use crate::template::runner::{DayRunner, Registry, RunOptions, StageRecord};
 use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET, Day};
 
 use super::{
@@ -7,32 +12,93 @@ use super::{
     timings::{Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
-    let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
+use child_commands::BufferedOutput;
 
-    let mut need_space = false;
+/// The stages of a day that finished, and the limit that stopped it early, if any.
+#[derive(Debug, Default)]
+pub struct DayRun {
+    pub records: Vec<StageRecord>,
+    pub limit: Option<Limit>,
+}
 
+/// Run the given days and print their results in order.
+/// With `jobs > 1`, that many days run at the same time and the output of each day is buffered until
+/// all days before it have been printed. These days run in their own process, as only the output of a child
+/// process can be buffered: a day run in this one would print its own output (and panics) straight away.
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    budget: Option<u64>,
+    jobs: usize,
+    limits: Limits,
+    registry: Option<&Registry>,
+) -> Option<Timings> {
     // NOTE: use non-duplicate, sorted day values.
-    all_days()
-        .filter(|day| days_to_run.contains(day))
-        .for_each(|day| {
-            if need_space {
-                println!();
-            }
-            need_space = true;
+    let days: Vec<Day> = all_days().filter(|day| days_to_run.contains(day)).collect();
 
-            println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
-            println!("------");
+    // a thread cannot be stopped from the outside, so limited days run in their own process.
+    let registry = registry.filter(|_| limits.is_empty());
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+    let mut timings: Vec<Timing> = Vec::with_capacity(days.len());
+    let mut stopped: Vec<(Day, Limit)> = vec![];
 
-            if output.is_empty() {
-                println!("Not solved.");
-            } else {
-                let val = child_commands::parse_exec_time(&output, day);
-                timings.push(val);
-            }
+    let mut report = |i: usize, day: Day, run: DayRun| {
+        if let Some(limit) = run.limit {
+            stopped.push((day, limit));
+        }
+
+        if run.records.is_empty() && run.limit.is_none() {
+            println!("Not solved.");
+        } else {
+            // a stopped day is recorded as such, so that its previous timings do not linger.
+            timings.push(Timing {
+                limit: run.limit,
+                ..child_commands::timing_from_records(&run.records, day)
+            });
+        }
+
+        if i + 1 < days.len() {
+            println!();
+        }
+    };
+
+    if jobs > 1 {
+        let run = |day: Day| {
+            child_commands::run_solution_buffered(day, is_timed, is_release, budget, limits)
+                .unwrap()
+        };
+
+        run_parallel(&days, jobs, run, |i, output| {
+            print_header(days[i]);
+            output.print();
+            report(i, days[i], output.run);
         });
+    } else {
+        for (i, &day) in days.iter().enumerate() {
+            print_header(day);
+
+            let run = match registry.and_then(|registry| registry.get(day)) {
+                Some(run_day) => DayRun {
+                    records: run_in_process(run_day, is_timed, budget),
+                    limit: None,
+                },
+                None => {
+                    child_commands::run_solution(day, is_timed, is_release, budget, limits).unwrap()
+                }
+            };
+
+            report(i, day, run);
+        }
+    }
+
+    if !stopped.is_empty() {
+        let stopped: Vec<String> = stopped
+            .iter()
+            .map(|(day, limit)| format!("Day {day} ({limit})"))
+            .collect();
+        println!("\n{ANSI_BOLD}Stopped:{ANSI_RESET} {}", stopped.join(", "));
+    }
 
     if is_timed {
         let timings = Timings { data: timings };
@@ -46,6 +112,70 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
     }
 }
 
+fn print_header(day: Day) {
+    println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+    println!("------");
+}
+
+/// Run `days` on `jobs` worker threads, calling `report` with the index and buffered output of each day in order.
+fn run_parallel(
+    days: &[Day],
+    jobs: usize,
+    run: impl Fn(Day) -> BufferedOutput + Sync,
+    mut report: impl FnMut(usize, BufferedOutput),
+) {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(days.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            let run = &run;
+
+            scope.spawn(move || {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&day) = days.get(i) else {
+                        break;
+                    };
+
+                    if sender.send((i, run(day))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // days finish in any order, hold on to their output until it is their turn.
+        let mut finished = BTreeMap::new();
+        let mut next_to_print = 0;
+
+        for (i, output) in receiver {
+            finished.insert(i, output);
+
+            while let Some(output) = finished.remove(&next_to_print) {
+                report(next_to_print, output);
+                next_to_print += 1;
+            }
+        }
+    });
+}
+
+/// Run a day linked into this binary. A panicking solution (e.g. a missing input) counts as not solved,
+/// as it would when run as a child process.
+fn run_in_process(run_day: DayRunner, is_timed: bool, budget: Option<u64>) -> Vec<StageRecord> {
+    let options = RunOptions {
+        timed: is_timed,
+        budget: budget.map_or(RunOptions::default().budget, Duration::from_millis),
+        json: false,
+        submit: None,
+    };
+
+    panic::catch_unwind(|| run_day(&options)).unwrap_or_default()
+}
+
 #[derive(Debug)]
 pub enum Error {
     BrokenPipe,
@@ -65,10 +195,14 @@ pub fn get_path_for_bin(day: Day) -> String {
 }
 
 /// All solutions live in isolated binaries.
-/// This module encapsulates interaction with these binaries, both invoking them as well as parsing the timing output.
+/// This module encapsulates interaction with these binaries, both invoking them as well as reading the records they report.
 pub mod child_commands {
-    use super::{Error, get_path_for_bin};
-    use crate::template::Day;
+    use super::{DayRun, Error, get_path_for_bin};
+    use crate::template::{
+        Day,
+        limits::{Limit, Limits},
+        runner::{Stage, StageRecord},
+    };
     use std::{
         io::{BufRead, BufReader},
         path::Path,
@@ -76,28 +210,86 @@ pub mod child_commands {
         thread,
     };
 
-    /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    /// Output of a day, buffered so that days run in parallel can be printed in order.
+    #[derive(Debug, Default)]
+    pub struct BufferedOutput {
+        pub run: DayRun,
+        stdout: String,
+        stderr: String,
+    }
+
+    impl BufferedOutput {
+        pub fn print(&self) {
+            print!("{}", self.stdout);
+            eprint!("{}", self.stderr);
+        }
+    }
+
+    /// Run the solution bin for a given day, printing its results as they come in.
+    /// The bin is run with `--json`, so each stage reports a `StageRecord` on its own line.
+    /// The bin stops itself when it exceeds `limits`, which is reported by its exit code.
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        budget: Option<u64>,
+        limits: Limits,
+    ) -> Result<DayRun, Error> {
+        run(day, is_timed, is_release, budget, limits, false).map(|output| output.run)
+    }
+
+    /// Run the solution bin for a given day, keeping its output to print later.
+    pub fn run_solution_buffered(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        budget: Option<u64>,
+        limits: Limits,
+    ) -> Result<BufferedOutput, Error> {
+        run(day, is_timed, is_release, budget, limits, true)
+    }
+
+    fn run(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        budget: Option<u64>,
+        limits: Limits,
+        is_buffered: bool,
+    ) -> Result<BufferedOutput, Error> {
+        let mut output = BufferedOutput::default();
+
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
-            return Ok(vec![]);
+            return Ok(output);
         }
 
         let day_padded = day.to_string();
+        let budget = budget.map(|millis| millis.to_string());
         let mut args = vec!["run", "--quiet", "--bin", &day_padded];
 
         if is_release {
             args.push("--release");
         }
 
+        args.push("--");
+        args.push("--json");
+
         if is_timed {
             // mirror `--time` flag to child invocations.
-            args.push("--");
             args.push("--time");
+
+            if let Some(budget) = &budget {
+                args.push("--budget");
+                args.push(budget);
+            }
         }
 
+        let limit_args = limits.to_args();
+        args.extend(limit_args.iter().map(String::as_str));
+
         // spawn child command with piped stdout/stderr.
-        // forward output to stdout/stderr while grabbing stdout lines.
+        // forward output to stdout/stderr (or the buffer) while grabbing stage records from stdout.
 
         let mut cmd = Command::new("cargo")
             .args(&args)
@@ -108,87 +300,90 @@ pub mod child_commands {
         let stdout = BufReader::new(cmd.stdout.take().ok_or(super::Error::BrokenPipe)?);
         let stderr = BufReader::new(cmd.stderr.take().ok_or(super::Error::BrokenPipe)?);
 
-        let mut output = vec![];
-
         let thread = thread::spawn(move || {
+            let mut buffer = String::new();
             stderr.lines().for_each(|line| {
-                eprintln!("{}", line.unwrap());
+                let line = line.unwrap();
+                if is_buffered {
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                } else {
+                    eprintln!("{line}");
+                }
             });
+            buffer
         });
 
         for line in stdout.lines() {
             let line = line.unwrap();
-            println!("{line}");
-            output.push(line);
+            // anything that is not a record (e.g. debug output of a solution) is passed through.
+            match line.parse::<StageRecord>() {
+                Ok(record) => {
+                    if is_buffered {
+                        output.stdout.push_str(&record.format_human());
+                    } else {
+                        record.print_human();
+                    }
+                    output.run.records.push(record);
+                }
+                Err(_) if is_buffered => {
+                    output.stdout.push_str(&line);
+                    output.stdout.push('\n');
+                }
+                Err(_) => println!("{line}"),
+            }
         }
 
-        thread.join().unwrap();
-        cmd.wait()?;
+        output.stderr = thread.join().unwrap();
+        output.run.limit = Limit::from_exit_code(cmd.wait()?.code());
 
         Ok(output)
     }
 
-    pub fn parse_exec_time(output: &[String], day: Day) -> super::Timing {
+    pub fn timing_from_records(records: &[StageRecord], day: Day) -> super::Timing {
         let mut timings = super::Timing {
             day,
+            parse: None,
             part_1: None,
             part_2: None,
+            parse_stats: None,
+            part_1_stats: None,
+            part_2_stats: None,
             total_nanos: 0_f64,
+            limit: None,
+            history: vec![],
         };
 
-        output
+        // unsolved parts have no meaningful timing.
+        let timed = records
             .iter()
-            .filter_map(|l| {
-                if !l.contains(" samples)") {
-                    return None;
-                }
+            .filter(|record| record.stage == Stage::Parse || record.answer.is_some());
 
-                let Some((timing_str, nanos)) = parse_time(l) else {
-                    eprintln!("Could not parse timings from line: {l}");
-                    return None;
-                };
-
-                let part = l.split(':').next()?;
-                Some((part, timing_str, nanos))
-            })
-            .for_each(|(part, timing_str, nanos)| {
-                if part.contains("Part 1") {
-                    timings.part_1 = Some(timing_str.into());
-                } else if part.contains("Part 2") {
-                    timings.part_2 = Some(timing_str.into());
+        for record in timed {
+            let timing_str = Some(format!("{:.1?}", record.duration));
+
+            match record.stage {
+                Stage::Parse => {
+                    timings.parse = timing_str;
+                    timings.parse_stats = record.stats;
+                }
+                Stage::Part(1) => {
+                    timings.part_1 = timing_str;
+                    timings.part_1_stats = record.stats;
                 }
+                Stage::Part(2) => {
+                    timings.part_2 = timing_str;
+                    timings.part_2_stats = record.stats;
+                }
+                Stage::Part(_) => continue,
+            }
 
-                timings.total_nanos += nanos;
-            });
+            timings.total_nanos += record.duration.as_nanos() as f64;
+        }
 
         timings
     }
 
-    fn parse_to_float(s: &str, postfix: &str) -> Option<f64> {
-        s.split(postfix).next()?.parse().ok()
-    }
-
-    fn parse_time(line: &str) -> Option<(&str, f64)> {
-        // for possible time formats, see: https://github.com/rust-lang/rust/blob/1.64.0/library/core/src/time.rs#L1176-L1200
-        let str_timing = line
-            .split(" samples)")
-            .next()?
-            .split('(')
-            .last()?
-            .split('@')
-            .next()?
-            .trim();
-
-        let parsed_timing = match str_timing {
-            s if s.contains("ns") => s.split("ns").next()?.parse::<f64>().ok(),
-            s if s.contains("µs") => parse_to_float(s, "µs").map(|x| x * 1000_f64),
-            s if s.contains("ms") => parse_to_float(s, "ms").map(|x| x * 1_000_000_f64),
-            s => parse_to_float(s, "s").map(|x| x * 1_000_000_000_f64),
-        }?;
-
-        Some((str_timing, parsed_timing))
-    }
-
     /// copied from: https://github.com/rust-lang/rust/blob/1.64.0/library/std/src/macros.rs#L328-L333
     #[cfg(feature = "test_lib")]
     macro_rules! assert_approx_eq {
@@ -205,53 +400,79 @@ pub mod child_commands {
 
     #[cfg(feature = "test_lib")]
     mod tests {
-        use super::parse_exec_time;
+        use super::timing_from_records;
+        use crate::template::runner::StageRecord;
 
         use crate::day;
 
+        fn parse_records(lines: &[&str]) -> Vec<StageRecord> {
+            lines.iter().map(|line| line.parse().unwrap()).collect()
+        }
+
         #[test]
-        fn parses_execution_times() {
-            let res = parse_exec_time(
-                &[
-                    "Part 1: 0 (74.13ns @ 100000 samples)".into(),
-                    "Part 2: 10 (74.13ms @ 99999 samples)".into(),
-                    "".into(),
-                ],
-                day!(1),
-            );
-            assert_approx_eq!(res.total_nanos, 74130074.13_f64);
-            assert_eq!(res.part_1.unwrap(), "74.13ns");
-            assert_eq!(res.part_2.unwrap(), "74.13ms");
+        fn reads_execution_times() {
+            let records = parse_records(&[
+                r#"{"stage":"part_1","status":"solved","answer":"0","nanos":74,"samples":100000,"stats":null}"#,
+                r#"{"stage":"part_2","status":"solved","answer":"10","nanos":74130000,"samples":99999,"stats":null}"#,
+            ]);
+            let res = timing_from_records(&records, day!(1));
+            assert_approx_eq!(res.total_nanos, 74130074_f64);
+            assert_eq!(res.part_1.unwrap(), "74.0ns");
+            assert_eq!(res.part_2.unwrap(), "74.1ms");
         }
 
         #[test]
-        fn parses_with_patterns_in_input() {
-            let res = parse_exec_time(
-                &[
-                    "Part 1: @ @ @ ( ) ms (2s @ 5 samples)".into(),
-                    "Part 2: 10s (100ms @ 1 samples)".into(),
-                    "".into(),
-                ],
-                day!(1),
+        fn reads_answers_with_patterns() {
+            let records = parse_records(&[
+                r#"{"stage":"part_1","status":"solved","answer":"@ @ @ ( ) ms (2s @ 5 samples)","nanos":2000000000,"samples":5,"stats":null}"#,
+                r#"{"stage":"part_2","status":"solved","answer":"10s\n(1ms)","nanos":100000000,"samples":1,"stats":null}"#,
+            ]);
+            assert_eq!(
+                records[0].answer.as_deref(),
+                Some("@ @ @ ( ) ms (2s @ 5 samples)")
             );
+            assert_eq!(records[1].answer.as_deref(), Some("10s\n(1ms)"));
+            let res = timing_from_records(&records, day!(1));
             assert_approx_eq!(res.total_nanos, 2100000000_f64);
-            assert_eq!(res.part_1.unwrap(), "2s");
-            assert_eq!(res.part_2.unwrap(), "100ms");
+            assert_eq!(res.part_1.unwrap(), "2.0s");
+            assert_eq!(res.part_2.unwrap(), "100.0ms");
         }
 
         #[test]
-        fn parses_missing_parts() {
-            let res = parse_exec_time(
-                &[
-                    "Part 1: ✖        ".into(),
-                    "Part 2: ✖        ".into(),
-                    "".into(),
-                ],
-                day!(1),
-            );
+        fn reads_bench_stats() {
+            let records = parse_records(&[
+                r#"{"stage":"parse","status":"parsed","answer":null,"nanos":2000000,"samples":10,"stats":null}"#,
+                r#"{"stage":"part_1","status":"solved","answer":"1","nanos":1500000,"samples":100,"stats":{"samples":95,"outliers":5,"min_nanos":1400000,"median_nanos":1500000,"p95_nanos":1600000,"stddev_nanos":20000}}"#,
+            ]);
+            let res = timing_from_records(&records, day!(1));
+            assert_approx_eq!(res.total_nanos, 3500000_f64);
+            assert_eq!(res.parse.unwrap(), "2.0ms");
+            assert_eq!(res.part_1.unwrap(), "1.5ms");
+            let stats = res.part_1_stats.unwrap();
+            assert_eq!(stats.samples, 95);
+            assert_eq!(stats.outliers, 5);
+            assert!(res.part_2.is_none());
+        }
+
+        #[test]
+        fn reads_missing_parts() {
+            let records = parse_records(&[
+                r#"{"stage":"part_1","status":"unsolved","answer":null,"nanos":10,"samples":1,"stats":null}"#,
+                r#"{"stage":"part_2","status":"unsolved","answer":null,"nanos":10,"samples":1,"stats":null}"#,
+            ]);
+            let res = timing_from_records(&records, day!(1));
             assert_approx_eq!(res.total_nanos, 0_f64);
             assert_eq!(res.part_1.is_none(), true);
             assert_eq!(res.part_2.is_none(), true);
         }
+
+        #[test]
+        fn rejects_human_output() {
+            assert!(
+                "Part 1: 0 (74.13ns @ 100000 samples)"
+                    .parse::<StageRecord>()
+                    .is_err()
+            );
+        }
     }
 }
@@ -1,35 +1,412 @@
 /// Encapsulates code that interacts with solution functions.
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::hint::black_box;
-use std::io::{Write, stdout};
+use std::io::{self, Write, stdout};
 use std::process::Output;
+use std::str::FromStr;
 use std::time::{Duration, Instant};
 use std::{cmp, env, process};
 
 use crate::template::ANSI_BOLD;
+use crate::template::answers::{Answers, get_path_for_answers};
 use crate::template::{ANSI_ITALIC, ANSI_RESET, Day, aoc_cli};
+use tinyjson::JsonValue;
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
-    let part_str = format!("Part {part}");
+/// How to run a solution: read from the command line for a day binary,
+/// or set by `run_multi` when the day is run in-process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunOptions {
+    /// Bench each stage instead of running it once.
+    pub timed: bool,
+    /// Time budget for benching a stage.
+    pub budget: Duration,
+    /// Report results as JSON lines (see `StageRecord`) rather than for humans.
+    pub json: bool,
+    /// The day of the binary, set by its `main` so that `--submit <part>` submits a part as soon as it is solved.
+    pub submit: Option<Day>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            timed: false,
+            budget: Duration::from_secs(1),
+            json: false,
+            submit: None,
+        }
+    }
+}
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+impl RunOptions {
+    /// Reads `--time`, `--budget <millis>` and `--json` (or `AOC_JSON`) from the command line.
+    pub fn from_args() -> Self {
+        let args: Vec<String> = env::args().collect();
+
+        let budget = args
+            .iter()
+            .position(|x| x == "--budget")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|millis| millis.parse().ok())
+            .map_or(RunOptions::default().budget, Duration::from_millis);
+
+        RunOptions {
+            timed: args.iter().any(|x| x == "--time"),
+            budget,
+            json: args.iter().any(|x| x == "--json") || env::var_os("AOC_JSON").is_some(),
+            submit: None,
+        }
+    }
+}
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+/// Entry point of a day linked into the running binary: the `run_day` generated by `solution!`.
+pub type DayRunner = fn(&RunOptions) -> Vec<StageRecord>;
 
-    if let Some(result) = result {
+/// Days linked into the running binary (see `src/registry.rs`, enabled by the `registry` feature),
+/// so that `all` and `time` can run them in-process instead of spawning `cargo run` for each day.
+#[derive(Clone, Default)]
+pub struct Registry {
+    days: Vec<(Day, DayRunner)>,
+}
+
+impl Registry {
+    pub fn new(days: Vec<(Day, DayRunner)>) -> Self {
+        Registry { days }
+    }
+
+    pub fn get(&self, day: Day) -> Option<DayRunner> {
+        self.days
+            .iter()
+            .find(|(d, _)| *d == day)
+            .map(|(_, run)| *run)
+    }
+}
+
+/// Run a solution part and report its result.
+pub fn solve_part<I: Clone, T: Display>(
+    options: &RunOptions,
+    func: impl Fn(I) -> Option<T>,
+    input: I,
+    part: u8,
+) -> StageRecord {
+    let stage = Stage::Part(part);
+
+    let (result, duration, stats) = run_timed(options, func, input, |result| {
+        if !options.json {
+            print_intermediate_result(result, &stage.to_string());
+        }
+    });
+
+    let record = StageRecord {
+        stage,
+        answer: result.as_ref().map(ToString::to_string),
+        duration,
+        stats,
+    };
+    record.print(options.json);
+
+    if let (Some(day), Some(result)) = (options.submit, result) {
         submit_result(result, day, part);
     }
+    record
+}
+
+/// Run the parse stage of a two-stage solution, timing it like a part.
+pub fn solve_parse<'a, P>(
+    options: &RunOptions,
+    func: impl Fn(&'a str) -> P,
+    input: &'a str,
+) -> (P, StageRecord) {
+    let (parsed, duration, stats) = run_timed(options, func, input, |_| {
+        if !options.json {
+            print!("{}: ✔", Stage::Parse);
+        }
+    });
+
+    let record = StageRecord {
+        stage: Stage::Parse,
+        answer: None,
+        duration,
+        stats,
+    };
+    record.print(options.json);
+    (parsed, record)
+}
+
+/// A stage of a solution run: parsing (for two-stage solutions) or one of the parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Part(u8),
+}
+
+impl Stage {
+    /// Name of the stage in JSON output.
+    fn key(self) -> String {
+        match self {
+            Stage::Parse => "parse".into(),
+            Stage::Part(part) => format!("part_{part}"),
+        }
+    }
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "Parse"),
+            Stage::Part(part) => write!(f, "Part {part}"),
+        }
+    }
+}
+
+/// The outcome of a single stage, printed as one JSON line per stage in `--json` mode:
+///
+/// `{"stage":"part_1","status":"solved","answer":"42","nanos":1500,"samples":98,"stats":{...}}`
+///
+/// `status` is `"solved"` or `"unsolved"` for parts and `"parsed"` for the parse stage.
+/// `stats` is `null` unless the stage was benched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StageRecord {
+    pub stage: Stage,
+    pub answer: Option<String>,
+    pub duration: Duration,
+    pub stats: Option<BenchStats>,
+}
+
+impl StageRecord {
+    fn status(&self) -> &'static str {
+        match (self.stage, &self.answer) {
+            (Stage::Parse, _) => "parsed",
+            (Stage::Part(_), Some(_)) => "solved",
+            (Stage::Part(_), None) => "unsolved",
+        }
+    }
+
+    fn samples(&self) -> usize {
+        self.stats.map_or(1, |stats| stats.samples + stats.outliers)
+    }
+
+    fn print(&self, is_json: bool) {
+        if is_json {
+            println!("{}", JsonValue::from(self).stringify().unwrap());
+        } else {
+            self.print_human();
+        }
+    }
+
+    /// Prints the final result line of the stage, overwriting the intermediate one.
+    pub fn print_human(&self) {
+        print!("\r{}", self.format_human());
+    }
+
+    /// The final result line(s) of the stage, as printed for humans.
+    pub fn format_human(&self) -> String {
+        let duration_str = format_duration(&self.duration, self.stats.as_ref());
+        match self.stage {
+            Stage::Parse => format!("{}: ✔{duration_str}\n", self.stage),
+            Stage::Part(_) => format_result(&self.answer, &self.stage.to_string(), &duration_str),
+        }
+    }
+}
+
+impl From<&StageRecord> for JsonValue {
+    fn from(value: &StageRecord) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert("stage".into(), JsonValue::String(value.stage.key()));
+        map.insert("status".into(), JsonValue::String(value.status().into()));
+        map.insert(
+            "answer".into(),
+            value
+                .answer
+                .clone()
+                .map_or(JsonValue::Null, JsonValue::String),
+        );
+        map.insert(
+            "nanos".into(),
+            JsonValue::Number(value.duration.as_nanos() as f64),
+        );
+        map.insert("samples".into(), JsonValue::Number(value.samples() as f64));
+        map.insert(
+            "stats".into(),
+            value
+                .stats
+                .as_ref()
+                .map_or(JsonValue::Null, JsonValue::from),
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl FromStr for StageRecord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = JsonValue::from_str(s).or(Err("Expected stage record to be valid JSON."))?;
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected stage record to be a JSON object.")?;
+
+        let stage = match json.get("stage").and_then(|v| v.get::<String>()) {
+            Some(s) if s == "parse" => Stage::Parse,
+            Some(s) => s
+                .strip_prefix("part_")
+                .and_then(|part| part.parse().ok())
+                .map(Stage::Part)
+                .ok_or("Expected record.stage to be `parse` or `part_N`.")?,
+            None => return Err("Expected record.stage to be a string.".into()),
+        };
+
+        let answer = json
+            .get("answer")
+            .map(|v| if v.is_null() { None } else { v.get::<String>() })
+            .ok_or("Expected record.answer to be null or string.")?;
+
+        let nanos = json
+            .get("nanos")
+            .and_then(|v| v.get::<f64>().copied())
+            .ok_or("Expected record.nanos to be a number.")?;
+
+        let stats = match json.get("stats") {
+            None => None,
+            Some(v) if v.is_null() => None,
+            Some(v) => Some(BenchStats::try_from(v)?),
+        };
+
+        Ok(StageRecord {
+            stage,
+            answer: answer.cloned(),
+            duration: Duration::from_nanos(nanos as u64),
+            stats,
+        })
+    }
+}
+
+/// Summary of a benchmark run, taken after warm-up and outlier rejection.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchStats {
+    /// Number of samples kept after outlier rejection.
+    pub samples: usize,
+    /// Number of samples rejected as outliers.
+    pub outliers: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub stddev: Duration,
+}
+
+/// Samples whose modified z-score (`0.6745 * |x - median| / MAD`) exceeds this are rejected.
+/// 3.5 is the cut-off recommended by Iglewicz and Hoaglin.
+const OUTLIER_THRESHOLD: f64 = 3.5;
+
+impl BenchStats {
+    /// Computes the statistics for a set of samples, rejecting outliers by median absolute deviation.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut nanos: Vec<f64> = samples.iter().map(|d| d.as_nanos() as f64).collect();
+        nanos.sort_unstable_by(f64::total_cmp);
+
+        let median = sorted_median(&nanos)?;
+        let mut deviations: Vec<f64> = nanos.iter().map(|x| (x - median).abs()).collect();
+        deviations.sort_unstable_by(f64::total_cmp);
+        let mad = sorted_median(&deviations)?;
+
+        // the median itself is never rejected, so `kept` is not empty.
+        // with a MAD of zero, (more than) half of all samples are identical and the score is undefined.
+        let kept: Vec<f64> = if mad > 0.0 {
+            nanos
+                .iter()
+                .copied()
+                .filter(|x| 0.6745 * (x - median).abs() / mad <= OUTLIER_THRESHOLD)
+                .collect()
+        } else {
+            nanos.clone()
+        };
+
+        let count = kept.len() as f64;
+        let mean = kept.iter().sum::<f64>() / count;
+        let variance = if kept.len() > 1 {
+            kept.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1.0)
+        } else {
+            0.0
+        };
+        // nearest-rank percentile.
+        let p95_index = ((0.95 * count).ceil() as usize).clamp(1, kept.len()) - 1;
+
+        Some(BenchStats {
+            samples: kept.len(),
+            outliers: nanos.len() - kept.len(),
+            min: from_nanos(kept[0]),
+            median: from_nanos(sorted_median(&kept)?),
+            p95: from_nanos(kept[p95_index]),
+            stddev: from_nanos(variance.sqrt()),
+        })
+    }
 }
 
-/// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
-///  1. in debug, the function is executed once.
-///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
+impl From<&BenchStats> for JsonValue {
+    fn from(value: &BenchStats) -> Self {
+        let nanos = |duration: Duration| JsonValue::Number(duration.as_nanos() as f64);
+
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+        map.insert("samples".into(), JsonValue::Number(value.samples as f64));
+        map.insert("outliers".into(), JsonValue::Number(value.outliers as f64));
+        map.insert("min_nanos".into(), nanos(value.min));
+        map.insert("median_nanos".into(), nanos(value.median));
+        map.insert("p95_nanos".into(), nanos(value.p95));
+        map.insert("stddev_nanos".into(), nanos(value.stddev));
+        JsonValue::Object(map)
+    }
+}
+
+impl TryFrom<&JsonValue> for BenchStats {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected stats to be a JSON object.")?;
+
+        let number = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<f64>().copied())
+                .ok_or(format!("Expected stats.{key} to be a number."))
+        };
+        let nanos = |key: &str| number(key).map(|n| Duration::from_nanos(n as u64));
+
+        Ok(BenchStats {
+            samples: number("samples")? as usize,
+            outliers: number("outliers")? as usize,
+            min: nanos("min_nanos")?,
+            median: nanos("median_nanos")?,
+            p95: nanos("p95_nanos")?,
+            stddev: nanos("stddev_nanos")?,
+        })
+    }
+}
+
+fn sorted_median(sorted: &[f64]) -> Option<f64> {
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+        _ => Some(sorted[mid]),
+    }
+}
+
+fn from_nanos(nanos: f64) -> Duration {
+    Duration::from_nanos(nanos.round() as u64)
+}
+
+/// Run a solution part once, passing the result to `hook` before anything else happens.
+/// With `options.timed` (`--time`), the part is then benched for about `options.budget` (`--budget <millis>`,
+/// 1 second by default), taking between 10 and 10000 samples, and the median is returned as its time.
 fn run_timed<I: Clone, T>(
+    options: &RunOptions,
     func: impl Fn(I) -> T,
     input: I,
     hook: impl Fn(&T),
-) -> (T, Duration, u128) {
+) -> (T, Duration, Option<BenchStats>) {
     let timer = Instant::now();
     let result = {
         let input = input.clone();
@@ -43,94 +420,88 @@ fn run_timed<I: Clone, T>(
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    if options.timed {
+        let stats = bench(options, func, input, &base_time);
+        (result, stats.median, Some(stats))
     } else {
-        (base_time, 1)
-    };
-
-    (result, run.0, run.1)
+        (result, base_time, None)
+    }
 }
 
-fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
-    let mut stdout = stdout();
+fn bench<I: Clone, T>(
+    options: &RunOptions,
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+) -> BenchStats {
+    let budget = options.budget;
+
+    if !options.json {
+        print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
+        let _ = stdout().flush();
+    }
 
-    print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+    let run = |input: &I| {
+        // need a clone here to make the borrow checker happy.
+        let cloned = input.clone();
+        let timer = Instant::now();
+        black_box(func(black_box(cloned)));
+        timer.elapsed()
+    };
 
-    let bench_iterations = cmp::min(
-        10000,
+    // warm up caches and the branch predictor for a tenth of the budget (at least one run).
+    // the fastest warm-up run is a better estimate of the cost of an iteration than the cold first run.
+    let warm_up_iterations = cmp::min(
+        100,
         cmp::max(
-            Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10),
-            10,
+            (budget / 10).as_nanos() / cmp::max(base_time.as_nanos(), 10),
+            1,
         ),
     );
+    let estimate = (0..warm_up_iterations)
+        .map(|_| run(&input))
+        .min()
+        .unwrap_or(*base_time);
 
-    let mut timers: Vec<Duration> = vec![];
+    let bench_iterations = cmp::min(
+        10000,
+        cmp::max(budget.as_nanos() / cmp::max(estimate.as_nanos(), 10), 10),
+    );
 
-    for _ in 0..bench_iterations {
-        // need a clone here to make the borrow checker happy.
-        let cloned = input.clone();
-        let timer = Instant::now();
-        black_box(func(black_box(cloned)));
-        timers.push(timer.elapsed());
-    }
+    let timers: Vec<Duration> = (0..bench_iterations).map(|_| run(&input)).collect();
 
-    (
-        #[allow(clippy::cast_possible_truncation)]
-        Duration::from_nanos(average_duration(&timers) as u64),
-        bench_iterations,
-    )
+    BenchStats::from_samples(&timers).expect("benching takes at least one sample")
 }
 
-fn average_duration(numbers: &[Duration]) -> u128 {
-    numbers
-        .iter()
-        .map(std::time::Duration::as_nanos)
-        .sum::<u128>()
-        / numbers.len() as u128
+fn format_duration(duration: &Duration, stats: Option<&BenchStats>) -> String {
+    match stats {
+        None => format!(" ({duration:.1?})"),
+        Some(stats) => format!(
+            " ({:.1?} ± {:.1?} @ {} samples, min {:.1?}, p95 {:.1?}, {} outliers)",
+            stats.median, stats.stddev, stats.samples, stats.min, stats.p95, stats.outliers
+        ),
+    }
 }
 
-fn format_duration(duration: &Duration, samples: u128) -> String {
-    if samples == 1 {
-        format!(" ({duration:.1?})")
-    } else {
-        format!(" ({duration:.1?} @ {samples} samples)")
+/// Prints the result of a stage before it is benched. `StageRecord::print_human` overwrites it once done.
+fn print_intermediate_result<T: Display>(result: &Option<T>, part: &str) {
+    match result {
+        Some(result) if result.to_string().contains('\n') => print!("{part}: ▼ "),
+        Some(result) => print!("{part}: {ANSI_BOLD}{result}{ANSI_RESET}"),
+        None => print!("{part}: ✖"),
     }
 }
 
-fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
-    let is_intermediate_result = duration_str.is_empty();
-
+fn format_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) -> String {
     match result {
         Some(result) => {
             if result.to_string().contains('\n') {
-                let str = format!("{part}: ▼ {duration_str}");
-                if is_intermediate_result {
-                    print!("{str}");
-                } else {
-                    print!("\r");
-                    println!("{str}");
-                    println!("{result}");
-                }
+                format!("{part}: ▼ {duration_str}\n{result}\n")
             } else {
-                let str = format!("{part}: {ANSI_BOLD}{result}{ANSI_RESET}{duration_str}");
-                if is_intermediate_result {
-                    print!("{str}");
-                } else {
-                    print!("\r");
-                    println!("{str}");
-                }
-            }
-        }
-        None => {
-            if is_intermediate_result {
-                print!("{part}: ✖");
-            } else {
-                print!("\r");
-                println!("{part}: ✖             ");
+                format!("{part}: {ANSI_BOLD}{result}{ANSI_RESET}{duration_str}\n")
             }
         }
+        None => format!("{part}: ✖             \n"),
     }
 }
 
@@ -172,5 +543,100 @@ fn submit_result<T: Display>(
     }
 
     println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    let answer = result.to_string();
+    let output = aoc_cli::submit(day, part, &answer);
+
+    if output.as_ref().is_ok_and(aoc_cli::is_accepted)
+        && confirm(&format!(
+            "Record this as the accepted answer to part {part} for `cargo verify`?"
+        ))
+    {
+        match Answers::record(day, part, &answer) {
+            Ok(()) => println!("Recorded answer in \"{}\".", get_path_for_answers(day)),
+            Err(e) => eprintln!("Failed to record answer: {e}"),
+        }
+    }
+
+    Some(output)
+}
+
+/// Asks a yes/no question on stdin, defaulting to yes.
+fn confirm(question: &str) -> bool {
+    print!("{question} [Y/n] ");
+    let _ = stdout().flush();
+
+    let mut reply = String::new();
+    if io::stdin().read_line(&mut reply).is_err() {
+        return false;
+    }
+
+    matches!(reply.trim().to_lowercase().as_str(), "" | "y" | "yes")
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{BenchStats, Stage, StageRecord};
+    use std::time::Duration;
+    use tinyjson::JsonValue;
+
+    #[test]
+    fn round_trips_records() {
+        let record = StageRecord {
+            stage: Stage::Part(2),
+            answer: Some("#..#\n.##. (1 @ 2 samples)".into()),
+            duration: Duration::from_micros(1500),
+            stats: Some(BenchStats {
+                samples: 9,
+                outliers: 1,
+                min: Duration::from_micros(1400),
+                median: Duration::from_micros(1500),
+                p95: Duration::from_micros(1700),
+                stddev: Duration::from_micros(90),
+            }),
+        };
+        let line = JsonValue::from(&record).stringify().unwrap();
+        assert!(!line.contains('\n'));
+        assert!(line.contains(r#""status":"solved""#));
+        assert!(line.contains(r#""samples":10"#));
+        assert_eq!(line.parse::<StageRecord>().unwrap(), record);
+
+        let parse = StageRecord {
+            stage: Stage::Parse,
+            answer: None,
+            duration: Duration::from_nanos(800),
+            stats: None,
+        };
+        let line = JsonValue::from(&parse).stringify().unwrap();
+        assert!(line.contains(r#""status":"parsed""#));
+        assert_eq!(line.parse::<StageRecord>().unwrap(), parse);
+    }
+
+    #[test]
+    fn rejects_outliers() {
+        let mut samples: Vec<Duration> = (0..19).map(|i| Duration::from_micros(100 + i)).collect();
+        samples.push(Duration::from_millis(50));
+
+        let stats = BenchStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.samples, 19);
+        assert_eq!(stats.outliers, 1);
+        assert_eq!(stats.min, Duration::from_micros(100));
+        assert_eq!(stats.median, Duration::from_micros(109));
+        assert_eq!(stats.p95, Duration::from_micros(118));
+        assert!(stats.stddev < Duration::from_micros(10));
+    }
+
+    #[test]
+    fn keeps_identical_samples() {
+        let samples = [Duration::from_micros(5); 4];
+        let stats = BenchStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.outliers, 0);
+        assert_eq!(stats.median, Duration::from_micros(5));
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn handles_no_samples() {
+        assert!(BenchStats::from_samples(&[]).is_none());
+    }
 }
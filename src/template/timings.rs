@@ -3,6 +3,9 @@ use std::{collections::HashMap, fs, io::Error, str::FromStr};
 use tinyjson::JsonValue;
 
 use crate::template::Day;
+use crate::template::history::{HISTORY_LENGTH, HistoryEntry, Provenance};
+use crate::template::limits::Limit;
+use crate::template::runner::BenchStats;
 
 static TIMINGS_FILE_PATH: &str = "./data/timings.json";
 
@@ -10,9 +13,17 @@ static TIMINGS_FILE_PATH: &str = "./data/timings.json";
 #[derive(Clone, Debug)]
 pub struct Timing {
     pub day: Day,
+    pub parse: Option<String>,
     pub part_1: Option<String>,
     pub part_2: Option<String>,
+    pub parse_stats: Option<BenchStats>,
+    pub part_1_stats: Option<BenchStats>,
+    pub part_2_stats: Option<BenchStats>,
     pub total_nanos: f64,
+    /// Set if the day was stopped by `--timeout` or `--max-memory`; only the stages that finished are timed.
+    pub limit: Option<Limit>,
+    /// Stored runs of the day, oldest first, ending with this run once it is recorded.
+    pub history: Vec<HistoryEntry>,
 }
 
 /// Represents benchmark times for a set of days.
@@ -46,11 +57,20 @@ impl Timings {
     }
 
     /// Merge two sets of timings, overwriting `self` with `other` if present.
+    /// The history of a day is kept, up to the last `HISTORY_LENGTH` runs.
     pub fn merge(&self, new: &Self) -> Self {
         let mut data: Vec<Timing> = vec![];
 
         for timing in &new.data {
-            data.push(timing.clone());
+            let mut timing = timing.clone();
+
+            if let Some(previous) = self.data.iter().find(|t| t.day == timing.day) {
+                timing.history = [previous.history.as_slice(), &timing.history].concat();
+            }
+
+            let excess = timing.history.len().saturating_sub(HISTORY_LENGTH);
+            timing.history.drain(..excess);
+            data.push(timing);
         }
 
         for timing in &self.data {
@@ -63,6 +83,14 @@ impl Timings {
         Timings { data }
     }
 
+    /// Adds the timings themselves as the latest run to the history of each day.
+    pub fn record_history(&mut self, provenance: &Provenance) {
+        for timing in &mut self.data {
+            let entry = HistoryEntry::new(timing, provenance);
+            timing.history.push(entry);
+        }
+    }
+
     /// Sum up total duration of timings as millis.
     pub fn total_millis(&self) -> f64 {
         self.data.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64
@@ -71,7 +99,7 @@ impl Timings {
     pub fn is_day_complete(&self, day: Day) -> bool {
         self.data
             .iter()
-            .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some())
+            .any(|t| t.day == day && t.part_1.is_some() && t.part_2.is_some() && t.limit.is_none())
     }
 }
 
@@ -122,9 +150,18 @@ impl From<&Timing> for JsonValue {
         map.insert("day".into(), JsonValue::String(value.day.to_string()));
         map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
 
+        let parse = value.parse.clone().map(JsonValue::String);
         let part_1 = value.part_1.clone().map(JsonValue::String);
         let part_2 = value.part_2.clone().map(JsonValue::String);
 
+        map.insert(
+            "parse".into(),
+            match parse {
+                Some(x) => x,
+                None => JsonValue::Null,
+            },
+        );
+
         map.insert(
             "part_1".into(),
             match part_1 {
@@ -141,10 +178,34 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        map.insert("parse_stats".into(), stats_to_json(value.parse_stats));
+        map.insert("part_1_stats".into(), stats_to_json(value.part_1_stats));
+        map.insert("part_2_stats".into(), stats_to_json(value.part_2_stats));
+        map.insert(
+            "limit".into(),
+            value.limit.map_or(JsonValue::Null, JsonValue::from),
+        );
+        map.insert(
+            "history".into(),
+            JsonValue::Array(value.history.iter().map(JsonValue::from).collect()),
+        );
+
         JsonValue::Object(map)
     }
 }
 
+fn stats_to_json(stats: Option<BenchStats>) -> JsonValue {
+    stats.as_ref().map_or(JsonValue::Null, JsonValue::from)
+}
+
+/// Timings stored before benchmarks were summarized have no `*_stats` key, which is read as `None`.
+fn stats_from_json(value: Option<&JsonValue>) -> Result<Option<BenchStats>, String> {
+    match value {
+        Some(value) if !value.is_null() => BenchStats::try_from(value).map(Some),
+        _ => Ok(None),
+    }
+}
+
 impl TryFrom<&JsonValue> for Timing {
     type Error = String;
 
@@ -174,11 +235,50 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // timings stored before parsing was timed separately have no `parse` key.
+        let parse = json
+            .get("parse")
+            .map_or(Some(None), |v| {
+                if v.is_null() {
+                    Some(None)
+                } else {
+                    v.get::<String>().map(Some)
+                }
+            })
+            .ok_or("Expected timing.parse to be null or string.")?;
+
+        let parse_stats = stats_from_json(json.get("parse_stats"))?;
+        let part_1_stats = stats_from_json(json.get("part_1_stats"))?;
+        let part_2_stats = stats_from_json(json.get("part_2_stats"))?;
+
+        // timings stored before limits were introduced have no `limit` key.
+        let limit = match json.get("limit") {
+            Some(v) if !v.is_null() => Some(Limit::try_from(v)?),
+            _ => None,
+        };
+
+        // timings stored before the history was kept have no `history` key.
+        let history = match json.get("history") {
+            Some(v) => v
+                .get::<Vec<JsonValue>>()
+                .ok_or("Expected timing.history to be an array.")?
+                .iter()
+                .map(HistoryEntry::try_from)
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+
         Ok(Timing {
             day,
+            parse: parse.cloned(),
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
+            parse_stats,
+            part_1_stats,
+            part_2_stats,
             total_nanos,
+            limit,
+            history,
         })
     }
 }
@@ -196,28 +296,48 @@ mod tests {
             data: vec![
                 Timing {
                     day: day!(1),
+                    parse: None,
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 3e+10,
+                    limit: None,
+                    history: vec![],
                 },
                 Timing {
                     day: day!(2),
+                    parse: None,
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 7e+10,
+                    limit: None,
+                    history: vec![],
                 },
                 Timing {
                     day: day!(4),
+                    parse: None,
                     part_1: Some("40ms".into()),
                     part_2: None,
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 4e+10,
+                    limit: None,
+                    history: vec![],
                 },
             ],
         }
     }
 
     mod deserialization {
+        use crate::template::limits::Limit;
         use crate::{day, template::timings::Timings};
+        use std::time::Duration;
 
         #[test]
         fn handles_json_timings() {
@@ -228,9 +348,41 @@ mod tests {
             assert_eq!(timing.day, day!(1));
             assert_eq!(timing.part_1, Some("1ms".to_string()));
             assert_eq!(timing.part_2, None);
+            assert_eq!(timing.part_1_stats, None);
+            assert_eq!(timing.parse, None);
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
         }
 
+        #[test]
+        fn handles_json_parse_timings() {
+            let json = r#"{ "data": [{ "day": "01", "parse": "3ms", "part_1": "1ms", "part_2": null, "total_nanos": 4000000, "parse_stats": null }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            assert_eq!(timing.parse, Some("3ms".to_string()));
+            assert_eq!(timing.parse_stats, None);
+        }
+
+        #[test]
+        fn handles_json_stats() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1.0ms", "part_2": null, "total_nanos": 1000000, "part_1_stats": { "samples": 98, "outliers": 2, "min_nanos": 900000, "median_nanos": 1000000, "p95_nanos": 1200000, "stddev_nanos": 50000 }, "part_2_stats": null }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let timing = timings.data.first().unwrap();
+            let stats = timing.part_1_stats.unwrap();
+            assert_eq!(stats.samples, 98);
+            assert_eq!(stats.outliers, 2);
+            assert_eq!(stats.median, Duration::from_millis(1));
+            assert_eq!(stats.stddev, Duration::from_micros(50));
+            assert_eq!(timing.part_2_stats, None);
+        }
+
+        #[test]
+        fn handles_json_limits() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000, "limit": "timeout" }, { "day": "02", "part_1": null, "part_2": null, "total_nanos": 0, "limit": null }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            assert_eq!(timings.data[0].limit, Some(Limit::Timeout));
+            assert_eq!(timings.data[1].limit, None);
+        }
+
         #[test]
         fn handles_empty_timings() {
             let json = r#"{ "data": [] }"#.to_string();
@@ -255,7 +407,9 @@ mod tests {
 
     mod serialization {
         use super::get_mock_timings;
+        use crate::template::{runner::BenchStats, timings::Timings};
         use std::collections::HashMap;
+        use std::time::Duration;
         use tinyjson::JsonValue;
 
         #[test]
@@ -274,6 +428,23 @@ mod tests {
                 3
             );
         }
+
+        #[test]
+        fn round_trips_stats() {
+            let mut timings = get_mock_timings();
+            timings.data[0].part_2_stats = Some(BenchStats {
+                samples: 10,
+                outliers: 1,
+                min: Duration::from_millis(19),
+                median: Duration::from_millis(20),
+                p95: Duration::from_millis(22),
+                stddev: Duration::from_micros(700),
+            });
+            let json = JsonValue::from(timings.clone()).stringify().unwrap();
+            let parsed = Timings::try_from(json).unwrap();
+            assert_eq!(parsed.data[0].part_2_stats, timings.data[0].part_2_stats);
+            assert_eq!(parsed.data[1].part_2_stats, None);
+        }
     }
 
     mod is_day_complete {
@@ -287,13 +458,19 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    parse: None,
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 3_000_000_000_f64,
+                    limit: None,
+                    history: vec![],
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -301,13 +478,19 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    parse: None,
                     part_1: Some("1ms".into()),
                     part_2: None,
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 1_000_000_000_f64,
+                    limit: None,
+                    history: vec![],
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -315,21 +498,29 @@ mod tests {
             let timings = Timings {
                 data: vec![Timing {
                     day: day!(1),
+                    parse: None,
                     part_1: None,
                     part_2: None,
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 0.0,
+                    limit: None,
+                    history: vec![],
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
     }
 
     mod merge {
         use crate::{
             day,
+            template::history::{HISTORY_LENGTH, Provenance},
             template::timings::{Timing, Timings},
         };
+        use tinyjson::JsonValue;
 
         use super::get_mock_timings;
 
@@ -339,9 +530,15 @@ mod tests {
             let other = Timings {
                 data: vec![Timing {
                     day: day!(3),
+                    parse: None,
                     part_1: None,
                     part_2: None,
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 0_f64,
+                    limit: None,
+                    history: vec![],
                 }],
             };
             let merged = timings.merge(&other);
@@ -359,9 +556,15 @@ mod tests {
             let other = Timings {
                 data: vec![Timing {
                     day: day!(2),
+                    parse: None,
                     part_1: None,
                     part_2: None,
+                    parse_stats: None,
+                    part_1_stats: None,
+                    part_2_stats: None,
                     total_nanos: 0_f64,
+                    limit: None,
+                    history: vec![],
                 }],
             };
             let merged = timings.merge(&other);
@@ -373,6 +576,32 @@ mod tests {
             assert_eq!(merged.data[2].day, day!(4));
         }
 
+        #[test]
+        fn keeps_bounded_history() {
+            let provenance = Provenance {
+                timestamp: 1_700_000_000,
+                commit: Some("3f2a1b7c".into()),
+                host: "0123456789abcdef".into(),
+            };
+
+            let mut timings = Timings::default();
+            for _ in 0..HISTORY_LENGTH + 5 {
+                let mut run = get_mock_timings();
+                run.record_history(&provenance);
+                timings = timings.merge(&run);
+            }
+
+            assert_eq!(timings.data[0].history.len(), HISTORY_LENGTH);
+            assert_eq!(
+                timings.data[0].history[0].commit.as_deref(),
+                Some("3f2a1b7c")
+            );
+
+            let json = JsonValue::from(timings.clone()).stringify().unwrap();
+            let parsed = Timings::try_from(json).unwrap();
+            assert_eq!(parsed.data[0].history, timings.data[0].history);
+        }
+
         #[test]
         fn handles_empty_timings() {
             let timings = Timings::default();